@@ -0,0 +1,47 @@
+/// Removes ANSI escape sequences, e.g. the colors of `terraform plan`, in place.
+pub fn strip(text: &mut String) {
+  if !text.contains('\x1b') {
+    return;
+  }
+
+  let mut stripped = String::with_capacity(text.len());
+  let mut chars = text.chars();
+
+  while let Some(ch) = chars.next() {
+    if ch != '\x1b' {
+      stripped.push(ch);
+      continue;
+    }
+
+    // CSI (e.g. SGR `ESC[1;32m`) runs until a final byte in `@..=~`, other
+    // sequences are a single character after ESC
+    if chars.next() == Some('[') {
+      for ch in chars.by_ref() {
+        if ('@'..='~').contains(&ch) {
+          break;
+        }
+      }
+    }
+  }
+
+  *text = stripped;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn strips_colors() {
+    let mut text = String::from("\x1b[1m  # aws_instance.a\x1b[0m will be \x1b[1;32mcreated\x1b[0m\n");
+    strip(&mut text);
+    assert_eq!(text, "  # aws_instance.a will be created\n");
+  }
+
+  #[test]
+  fn strips_other_sequences() {
+    let mut text = String::from("\x1b=a\x1b[?25lb\x1b[Kc é");
+    strip(&mut text);
+    assert_eq!(text, "abc é");
+  }
+}
@@ -0,0 +1,59 @@
+use std::{fs::OpenOptions, io::Write};
+
+/// Copies `text` to the system clipboard with the OSC 52 escape sequence,
+/// which the terminal handles, so it also works over SSH.
+pub fn copy(text: &str) -> std::io::Result<()> {
+  let mut tty = OpenOptions::new().write(true).open("/dev/tty")?;
+  write!(tty, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+  tty.flush()
+}
+
+fn base64(bytes: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+  let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+  for chunk in bytes.chunks(3) {
+    let group = chunk
+      .iter()
+      .enumerate()
+      .fold(0u32, |group, (idx, byte)| group | u32::from(*byte) << (16 - 8 * idx));
+
+    for idx in 0..4 {
+      // one more character than bytes in the chunk, padded to 4
+      if idx <= chunk.len() {
+        encoded.push(ALPHABET[(group >> (18 - 6 * idx) & 0x3f) as usize] as char);
+      } else {
+        encoded.push('=');
+      }
+    }
+  }
+  encoded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn base64_padding() {
+    // RFC 4648 test vectors
+    let cases = [
+      ("", ""),
+      ("f", "Zg=="),
+      ("fo", "Zm8="),
+      ("foo", "Zm9v"),
+      ("foob", "Zm9vYg=="),
+      ("fooba", "Zm9vYmE="),
+      ("foobar", "Zm9vYmFy"),
+    ];
+    for (text, encoded) in cases {
+      assert_eq!(base64(text.as_bytes()), encoded);
+    }
+  }
+
+  #[test]
+  fn base64_all_bits() {
+    assert_eq!(base64(&[0xff, 0xfe, 0xfd, 0x00]), "//79AA==");
+    assert_eq!(base64("é".as_bytes()), "w6k=");
+  }
+}
@@ -0,0 +1,475 @@
+use crate::{Action, ActionType, Attribute, AttributeChange, Output, ParseError, Plan};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt::Write, io::Read};
+
+// subset of `terraform show -json` output, see
+// https://www.terraform.io/docs/internals/json-format.html
+#[derive(Debug, Deserialize)]
+struct JsonPlan {
+  #[serde(default)]
+  resource_changes: Vec<ResourceChange>,
+  #[serde(default)]
+  resource_drift: Vec<ResourceChange>,
+  #[serde(default)]
+  output_changes: BTreeMap<String, Change>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceChange {
+  address: String,
+  #[serde(default)]
+  mode: String,
+  #[serde(rename = "type")]
+  typ: String,
+  name: String,
+  #[serde(default)]
+  previous_address: Option<String>,
+  change: Change,
+}
+
+#[derive(Debug, Deserialize)]
+struct Change {
+  actions: Vec<String>,
+  #[serde(default)]
+  before: Value,
+  #[serde(default)]
+  after: Value,
+  #[serde(default)]
+  after_unknown: Value,
+  #[serde(default)]
+  before_sensitive: Value,
+  #[serde(default)]
+  after_sensitive: Value,
+  #[serde(default)]
+  importing: Option<Importing>,
+  #[serde(default)]
+  replace_paths: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Importing {
+  #[serde(default)]
+  id: Option<String>,
+}
+
+pub fn read_plan(reader: impl Read, lenient: bool) -> Result<Plan> {
+  let json: JsonPlan = serde_json::from_reader(reader).context("expecting JSON plan")?;
+
+  let mut plan = Plan::default();
+
+  for (changes, actions) in [
+    (json.resource_changes, &mut plan.actions),
+    (json.resource_drift, &mut plan.drift),
+  ] {
+    for rc in changes {
+      let address = rc.address.clone();
+      match read_action(rc) {
+        Ok(Some(action)) => actions.push(action),
+        Ok(None) => {}
+        Err(err) if lenient => plan.warnings.push(ParseError {
+          line: 0,
+          column: 0,
+          text: address,
+          message: format!("{:#}", err),
+        }),
+        Err(err) => return Err(err.context(address)),
+      }
+    }
+  }
+
+  for (name, change) in json.output_changes {
+    let address = format!("output.{}", name);
+    match read_output(name, change) {
+      Ok(Some(output)) => plan.outputs.push(output),
+      Ok(None) => {}
+      Err(err) if lenient => plan.warnings.push(ParseError {
+        line: 0,
+        column: 0,
+        text: address,
+        message: format!("{:#}", err),
+      }),
+      Err(err) => return Err(err.context(address)),
+    }
+  }
+
+  Ok(plan)
+}
+
+// `None` for unchanged outputs
+fn read_output(name: String, change: Change) -> Result<Option<Output>> {
+  let actions_text: Vec<&str> = change.actions.iter().map(String::as_str).collect();
+
+  let typ = match actions_text.as_slice() {
+    ["no-op"] => return Ok(None),
+    ["create"] => ActionType::Create,
+    ["update"] => ActionType::Update,
+    ["delete"] => ActionType::Destroy,
+    _ => bail!("unexpected Output change: {:?}", actions_text),
+  };
+
+  let sensitive = change.before_sensitive == Value::Bool(true) || change.after_sensitive == Value::Bool(true);
+  let render = |value: &Value, unknown: bool| {
+    if sensitive {
+      "(sensitive value)".to_string()
+    } else if unknown {
+      "(known after apply)".to_string()
+    } else {
+      value.to_string()
+    }
+  };
+
+  let old = render(&change.before, false);
+  let new = render(&change.after, change.after_unknown == Value::Bool(true));
+
+  let content = match typ {
+    ActionType::Create => format!("  + {} = {}\n", name, new),
+    ActionType::Destroy => format!("  - {} = {} -> null\n", name, old),
+    _ => format!("  ~ {} = {} -> {}\n", name, old, new),
+  };
+
+  Ok(Some(Output {
+    typ,
+    name,
+    content,
+    sensitive,
+  }))
+}
+
+// `None` for resources without any change
+fn read_action(rc: ResourceChange) -> Result<Option<Action>> {
+  let actions_text: Vec<&str> = rc.change.actions.iter().map(String::as_str).collect();
+
+  let import_id = rc.change.importing.as_ref().and_then(|i| i.id.clone());
+
+  let typ = match actions_text.as_slice() {
+    ["no-op"] if rc.change.importing.is_some() => ActionType::Import,
+    ["no-op"] if rc.previous_address.is_some() => ActionType::Move,
+    ["no-op"] => return Ok(None),
+    ["create"] => ActionType::Create,
+    ["update"] => ActionType::Update,
+    ["delete"] => ActionType::Destroy,
+    ["delete", "create"] => ActionType::DestroyThenCreate,
+    ["create", "delete"] => ActionType::DuplicateThenRemove,
+    ["read"] => ActionType::Read,
+    ["forget"] => ActionType::Forget,
+    _ => bail!("unexpected Action type: {:?}", actions_text),
+  };
+
+  // same notes as the text plan prints below the header
+  let mut content = String::new();
+  if let Some(from) = &rc.previous_address {
+    let _ = writeln!(content, "  # (moved from {})", from);
+  }
+  if let Some(id) = &import_id {
+    let _ = writeln!(content, "  # (imported from \"{}\")", id);
+  }
+  let attributes = read_attributes(&typ, &rc.change);
+
+  // same block as in the text plan
+  let symbol = match typ {
+    ActionType::Move | ActionType::Import => "   ".to_string(),
+    _ => typ.to_string(),
+  };
+  let mode = if rc.mode == "data" { "data" } else { "resource" };
+  let _ = writeln!(content, "{} {} \"{}\" \"{}\" {{", symbol, mode, rc.typ, rc.name);
+  render_attributes(&attributes, 6, &mut content);
+  let _ = writeln!(content, "    }}");
+
+  Ok(Some(Action {
+    typ,
+    reference: rc.address,
+    resource: rc.typ,
+    name: rc.name,
+    content,
+    forces_replacement: attributes
+      .iter()
+      .flat_map(Attribute::forces_replacement_paths)
+      .collect(),
+    attributes,
+    moved_from: rc.previous_address,
+    import_id,
+  }))
+}
+
+fn read_attributes(typ: &ActionType, change: &Change) -> Vec<Attribute> {
+  let replace_paths: Vec<String> = change.replace_paths.iter().map(|path| render_path(path)).collect();
+
+  // forgotten resources are left as they are
+  let after = match typ {
+    ActionType::Forget => &change.before,
+    _ => &change.after,
+  };
+
+  let root = Values {
+    before: &change.before,
+    after,
+    after_unknown: &change.after_unknown,
+    before_sensitive: &change.before_sensitive,
+    after_sensitive: &change.after_sensitive,
+  };
+
+  root.children("", &replace_paths)
+}
+
+// the same position in each of the trees of a change
+#[derive(Clone, Copy)]
+struct Values<'a> {
+  before: &'a Value,
+  after: &'a Value,
+  after_unknown: &'a Value,
+  before_sensitive: &'a Value,
+  after_sensitive: &'a Value,
+}
+
+impl<'a> Values<'a> {
+  fn get(&self, key: &str) -> Values<'a> {
+    Values {
+      before: self.before.get(key).unwrap_or(&Value::Null),
+      after: self.after.get(key).unwrap_or(&Value::Null),
+      after_unknown: self.after_unknown.get(key).unwrap_or(&Value::Null),
+      before_sensitive: self.before_sensitive.get(key).unwrap_or(&Value::Null),
+      after_sensitive: self.after_sensitive.get(key).unwrap_or(&Value::Null),
+    }
+  }
+
+  fn index(&self, idx: usize) -> Values<'a> {
+    Values {
+      before: self.before.get(idx).unwrap_or(&Value::Null),
+      after: self.after.get(idx).unwrap_or(&Value::Null),
+      after_unknown: self.after_unknown.get(idx).unwrap_or(&Value::Null),
+      before_sensitive: self.before_sensitive.get(idx).unwrap_or(&Value::Null),
+      after_sensitive: self.after_sensitive.get(idx).unwrap_or(&Value::Null),
+    }
+  }
+
+  fn unknown(&self) -> bool {
+    self.after_unknown == &Value::Bool(true)
+  }
+
+  fn sensitive(&self) -> bool {
+    self.before_sensitive == &Value::Bool(true) || self.after_sensitive == &Value::Bool(true)
+  }
+
+  fn children(&self, path: &str, replace_paths: &[String]) -> Vec<Attribute> {
+    let mut keys: Vec<&String> = Vec::new();
+    for value in &[self.before, self.after, self.after_unknown] {
+      if let Some(object) = value.as_object() {
+        keys.extend(object.keys());
+      }
+    }
+
+    if !keys.is_empty() {
+      keys.sort();
+      keys.dedup();
+      return keys
+        .into_iter()
+        .filter_map(|key| self.get(key).attribute(key, path, replace_paths))
+        .collect();
+    }
+
+    let len = [self.before, self.after, self.after_unknown]
+      .iter()
+      .filter_map(|value| value.as_array())
+      .map(Vec::len)
+      .max()
+      .unwrap_or_default();
+
+    (0..len)
+      .filter_map(|idx| self.index(idx).attribute("", path, replace_paths))
+      .collect()
+  }
+
+  // `None` if there is no value at all
+  fn attribute(&self, name: &str, parent_path: &str, replace_paths: &[String]) -> Option<Attribute> {
+    let before = Some(self.before).filter(|v| !v.is_null());
+    let after = Some(self.after).filter(|v| !v.is_null());
+    let unknown = self.unknown();
+
+    let change = match (before, after) {
+      (None, None) if !unknown => return None,
+      (None, _) => AttributeChange::Add,
+      (Some(_), None) if !unknown => AttributeChange::Remove,
+      (Some(old), Some(new)) if old == new && !unknown => AttributeChange::Unchanged,
+      _ => AttributeChange::Update,
+    };
+
+    let mut attr = Attribute::new(name, parent_path, change);
+    attr.forces_replacement = !name.is_empty() && replace_paths.contains(&attr.path);
+    attr.sensitive = self.sensitive();
+    attr.known_after_apply = unknown;
+
+    let is_collection = |v: Option<&Value>| v.is_some_and(|v| v.is_object() || v.is_array());
+    if !attr.sensitive && !unknown && (is_collection(before) || is_collection(after)) {
+      attr.children = self.children(&attr.path, replace_paths);
+      if !attr.children.is_empty() {
+        return Some(attr);
+      }
+    }
+
+    let render = |value: Option<&Value>, sensitive: bool| {
+      value.map(|value| {
+        if sensitive {
+          "(sensitive value)".to_string()
+        } else {
+          value.to_string()
+        }
+      })
+    };
+
+    attr.old = render(before, self.before_sensitive == &Value::Bool(true));
+    attr.new = if unknown {
+      Some("(known after apply)".to_string())
+    } else {
+      render(after, self.after_sensitive == &Value::Bool(true))
+    };
+
+    Some(attr)
+  }
+}
+
+// renders attributes in the same layout as the text plan, i.e. the lines
+// between the resource header and its closing brace
+fn render_attributes(attributes: &[Attribute], indent: usize, content: &mut String) {
+  for attr in attributes {
+    let name = if attr.name.is_empty() {
+      String::new()
+    } else {
+      format!("{} = ", attr.name)
+    };
+    let comment = if attr.forces_replacement {
+      " # forces replacement"
+    } else {
+      ""
+    };
+
+    if !attr.children.is_empty() {
+      // only lists have elements without names
+      let (open, close) = if attr.children[0].name.is_empty() {
+        ('[', ']')
+      } else {
+        ('{', '}')
+      };
+      let _ = writeln!(
+        content,
+        "{:indent$}{} {}{}{}",
+        "",
+        attr.change,
+        name,
+        open,
+        comment,
+        indent = indent
+      );
+      render_attributes(&attr.children, indent + 4, content);
+      let _ = writeln!(content, "{:indent$}{}", "", close, indent = indent + 2);
+      continue;
+    }
+
+    let value = match (attr.change, &attr.old, &attr.new) {
+      (AttributeChange::Remove, Some(old), _) => format!("{} -> null", old),
+      (AttributeChange::Update, Some(old), Some(new)) => format!("{} -> {}", old, new),
+      (_, _, Some(new)) => new.clone(),
+      (_, Some(old), None) => old.clone(),
+      (_, None, None) => "null".to_string(),
+    };
+    let separator = if attr.name.is_empty() { "," } else { "" };
+
+    let _ = writeln!(
+      content,
+      "{:indent$}{} {}{}{}{}",
+      "",
+      attr.change,
+      name,
+      value,
+      separator,
+      comment,
+      indent = indent
+    );
+  }
+}
+
+// example: ["root_block_device", 0, "volume_size"] -> root_block_device.volume_size
+fn render_path(path: &[Value]) -> String {
+  path
+    .iter()
+    .filter_map(|step| match step {
+      Value::String(s) => Some(s.clone()),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(json: &str) -> Plan {
+    read_plan(json.as_bytes(), false).unwrap()
+  }
+
+  #[test]
+  fn drift_only() {
+    let plan = parse(
+      r#"{"resource_drift": [{
+        "address": "aws_instance.x", "mode": "managed", "type": "aws_instance", "name": "x",
+        "change": {"actions": ["delete"], "before": {"id": "i-1"}, "after": null}
+      }]}"#,
+    );
+
+    assert!(plan.actions.is_empty());
+    assert_eq!(plan.drift.len(), 1);
+    assert_eq!(plan.drift[0].typ, ActionType::Destroy);
+  }
+
+  #[test]
+  fn outputs_only() {
+    let plan = parse(
+      r#"{"output_changes": {
+        "foo": {"actions": ["create"], "before": null, "after": "bar"},
+        "same": {"actions": ["no-op"], "before": 1, "after": 1}
+      }}"#,
+    );
+
+    assert!(plan.actions.is_empty());
+    assert_eq!(plan.outputs.len(), 1);
+    assert_eq!(plan.outputs[0].content, "  + foo = \"bar\"\n");
+  }
+
+  #[test]
+  fn replace_paths_force_replacement() {
+    let plan = parse(
+      r#"{"resource_changes": [{
+        "address": "aws_db_instance.db", "mode": "managed", "type": "aws_db_instance", "name": "db",
+        "change": {
+          "actions": ["delete", "create"],
+          "before": {"engine": "mysql", "storage": {"size": 8}},
+          "after": {"engine": "postgres", "storage": {"size": 10}},
+          "replace_paths": [["engine"], ["storage", "size"]]
+        }
+      }]}"#,
+    );
+
+    let action = &plan.actions[0];
+    assert_eq!(action.typ, ActionType::DestroyThenCreate);
+    assert_eq!(action.forces_replacement, ["engine", "storage.size"]);
+    assert!(action
+      .content
+      .contains("engine = \"mysql\" -> \"postgres\" # forces replacement"));
+  }
+
+  #[test]
+  fn lenient_outputs() {
+    let json = r#"{"output_changes": {
+      "bad": {"actions": ["delete", "create"], "before": 1, "after": 2},
+      "foo": {"actions": ["create"], "before": null, "after": "bar"}
+    }}"#;
+    assert!(read_plan(json.as_bytes(), false).is_err());
+
+    let plan = read_plan(json.as_bytes(), true).unwrap();
+    assert_eq!(plan.outputs.len(), 1);
+    assert_eq!(plan.warnings.len(), 1);
+    assert_eq!(plan.warnings[0].text, "output.bad");
+  }
+}
@@ -0,0 +1,63 @@
+//! Parser for Terraform plans, both the human-readable `terraform plan`
+//! output and the machine-readable `terraform show -json`.
+
+use anyhow::Result;
+use std::io::BufRead;
+
+mod ansi;
+mod json;
+mod plan;
+mod quote;
+mod text;
+
+pub use plan::{Action, ActionType, Attribute, AttributeChange, Output, ParseError, Plan, Summary};
+
+/// How to parse a plan, the defaults detect the format and fail on the first
+/// error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+  /// Expect a JSON plan instead of detecting it.
+  pub json: bool,
+  /// Skip Actions that can't be parsed instead of failing, they are listed in
+  /// `Plan::warnings`.
+  pub lenient: bool,
+}
+
+/// Parses a plan, detecting whether it is JSON or text.
+pub fn parse_plan(reader: impl BufRead) -> Result<Plan> {
+  parse_plan_with(reader, ParseOptions::default())
+}
+
+/// Parses a plan with the given options.
+pub fn parse_plan_with(mut reader: impl BufRead, options: ParseOptions) -> Result<Plan> {
+  if options.json || input_is_json(&mut reader)? {
+    json::read_plan(reader, options.lenient)
+  } else {
+    text::read_plan(reader, options.lenient)
+  }
+}
+
+/// Parses a `terraform show -json` plan.
+pub fn parse_json_plan(reader: impl BufRead) -> Result<Plan> {
+  json::read_plan(reader, false)
+}
+
+/// Parses a `terraform plan` text output.
+pub fn parse_text_plan(reader: impl BufRead) -> Result<Plan> {
+  text::read_plan(reader, false)
+}
+
+// JSON plans (`terraform show -json`) are a single object
+fn input_is_json(input: &mut impl BufRead) -> Result<bool> {
+  loop {
+    let buf = input.fill_buf()?;
+    match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+      Some(pos) => return Ok(buf[pos] == b'{'),
+      None if buf.is_empty() => return Ok(false),
+      None => {
+        let len = buf.len();
+        input.consume(len);
+      }
+    }
+  }
+}
@@ -1,157 +1,102 @@
 use anyhow::{bail, Context, Result};
-use cursive::{
-  event::Key,
-  traits::{Nameable, Resizable, Scrollable},
-  views::{LinearLayout, SelectView, TextView},
+use std::{
+  env,
+  fs::File,
+  io::{self, BufRead, BufReader},
 };
-use std::{fmt, io};
-
-#[derive(Debug, Clone)]
-enum ActionType {
-  Create,
-  Update,
-  Destroy,
-  DestroyThenCreate,
-  DuplicateThenRemove,
-}
-
-impl fmt::Display for ActionType {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      ActionType::Create => write!(f, "  +"),
-      ActionType::Update => write!(f, "  ~"),
-      ActionType::Destroy => write!(f, "  -"),
-      ActionType::DestroyThenCreate => write!(f, "-/+"),
-      ActionType::DuplicateThenRemove => write!(f, "+/-"),
-    }
-  }
+use vp::ParseOptions;
+
+mod clipboard;
+mod report;
+mod review;
+mod ui;
+
+// what to do with the plan
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Mode {
+  #[default]
+  View,
+  Summary,
+  Markdown,
 }
 
-#[derive(Debug, Clone)]
-struct Action {
-  typ: ActionType,
-  // example: module.abc.aws_iam_policy.name[key]
-  reference: String,
-  // example: aws_iam_policy
-  resource: String,
-  // example: name
-  name: String,
-  content: String,
+#[derive(Debug, Default)]
+struct Args {
+  options: ParseOptions,
+  mode: Mode,
+  // of the Markdown report, `report::MAX_SIZE` if `None`
+  max_size: Option<usize>,
+  // `None` or "-" reads from stdin
+  path: Option<String>,
 }
 
-fn main() -> Result<()> {
-  let mut line = String::new();
-  // skip to actions start or no changes
-  while io::stdin().read_line(&mut line)? > 0 {
-    if line.starts_with("Terraform will perform the following actions:") {
-      break;
+impl Args {
+  fn parse() -> Result<Self> {
+    let mut args = Args::default();
+
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+      match arg.as_str() {
+        "--json" => args.options.json = true,
+        "--lenient" => args.options.lenient = true,
+        "--summary" => args.mode = Mode::Summary,
+        "--format" => match argv.next().as_deref() {
+          Some("markdown") => args.mode = Mode::Markdown,
+          Some(format) => bail!("unknown format: {}, expecting markdown", format),
+          None => bail!("expecting a format after --format"),
+        },
+        "--max-size" => {
+          let size = argv.next().context("expecting a size after --max-size")?;
+          let size: usize = size.parse().with_context(|| format!("invalid size: {}", size))?;
+          if size < report::MIN_SIZE {
+            bail!("--max-size has to be at least {}", report::MIN_SIZE);
+          }
+          args.max_size = Some(size);
+        }
+        _ if arg.starts_with('-') && arg != "-" => bail!("unknown option: {}", arg),
+        _ => {
+          if args.path.is_some() {
+            bail!("unexpected argument: {}", arg);
+          }
+          args.path = Some(arg);
+        }
+      }
     }
 
-    if line.starts_with("No changes. Infrastructure is up-to-date.") {
-      return Ok(());
-    }
-    line.clear();
+    Ok(args)
   }
 
-  let mut actions = Vec::new();
-
-  // read first action
-  while io::stdin().read_line(&mut line)? > 0 {
-    if line.starts_with("  # ") {
-      break;
+  fn open(&self) -> Result<Box<dyn BufRead>> {
+    match self.path.as_deref() {
+      None | Some("-") => Ok(Box::new(io::stdin().lock())),
+      Some(path) => {
+        let file = File::open(path).with_context(|| format!("opening plan file {}", path))?;
+        Ok(Box::new(BufReader::new(file)))
+      }
     }
-    line.clear();
   }
+}
 
-  let mut this_action = read_action_header(&mut line).context("expect first Action")?;
-
-  loop {
-    if line.starts_with("  # ") {
-      this_action = read_action_header(&mut line).context("expect header for Action")?;
-    } else if line.starts_with("Plan: ") {
-      break;
-    } else {
-      // end of an action
-      if line.starts_with("    }") {
-        // the `clone` is just for compiler
-        actions.push(this_action.clone());
-      } else {
-        this_action.content.push_str(&line);
-      }
-    }
+fn main() -> Result<()> {
+  let args = Args::parse()?;
 
-    line.clear();
+  let plan = vp::parse_plan_with(args.open()?, args.options)?;
 
-    if io::stdin().read_line(&mut line)? == 0 {
-      break;
+  if args.mode != Mode::View {
+    for warning in &plan.warnings {
+      eprintln!("warning: {}", warning);
     }
   }
 
-  let mut ui = cursive::default();
-  ui.add_global_callback('q', |s| s.quit());
-  ui.add_global_callback(Key::Esc, |s| s.quit());
-
-  let content = TextView::new(actions[0].content.clone())
-    .with_name("content")
-    .max_width(120)
-    .scrollable();
-
-  let mut select = SelectView::new().on_select(|this_ui, content| {
-    this_ui.call_on_name("content", |view: &mut TextView| {
-      view.set_content(content);
-    });
-  });
-
-  for act in actions {
-    select.add_item(format!("{} {} {}", act.typ, act.resource, act.name), act.content);
+  match args.mode {
+    Mode::View => ui::run(plan),
+    Mode::Summary => {
+      print!("{}", report::summary(&plan));
+      Ok(())
+    }
+    Mode::Markdown => {
+      print!("{}", report::markdown(&plan, args.max_size.unwrap_or(report::MAX_SIZE)));
+      Ok(())
+    }
   }
-
-  ui.add_layer(LinearLayout::horizontal().child(select).child(content));
-  ui.run();
-
-  Ok(())
-}
-
-fn read_action_header(mut line: &mut String) -> Result<Action> {
-  // TODO: handle whitespace in key
-  let reference = line
-    .split_whitespace()
-    .nth(1)
-    .context("expecting resource reference")?
-    .to_string();
-
-  line.clear();
-  io::stdin().read_line(&mut line).context("expecting Action detail")?;
-
-  let (typ_text, rest) = line.split_at(4);
-
-  let typ = match typ_text {
-    "  + " => ActionType::Create,
-    "  ~ " => ActionType::Update,
-    "  - " => ActionType::Destroy,
-    "-/+ " => ActionType::DestroyThenCreate,
-    "+/- " => ActionType::DuplicateThenRemove,
-    _ => bail!("unexpected Action type: {}", typ_text),
-  };
-
-  // remove 'resources '
-  let (_, rest) = rest.split_at(9);
-
-  let rest = rest.trim();
-
-  let rest = rest.strip_prefix("\"").context("expecting beginning quote")?;
-  let rest = rest.strip_suffix("\" {").context("expecting ending quote")?;
-  let mut parts = rest.split("\" \"");
-  let resource = parts.next().context("expecting resource")?.to_string();
-  let name = parts.next().context("expecting name")?.to_string();
-
-  line.clear();
-
-  Ok(Action {
-    typ,
-    reference,
-    resource,
-    name,
-    content: String::new(),
-  })
 }
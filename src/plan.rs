@@ -0,0 +1,300 @@
+use crate::quote::Unquoted;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActionType {
+  Create,
+  Update,
+  Destroy,
+  DestroyThenCreate,
+  DuplicateThenRemove,
+  /// Data source read during apply.
+  Read,
+  /// Only the address changes, see `Action::moved_from`.
+  Move,
+  /// Existing object brought under management, see `Action::import_id`.
+  Import,
+  /// Removed from the state without being destroyed.
+  Forget,
+}
+
+impl ActionType {
+  /// Short description, e.g. for reports.
+  pub fn name(&self) -> &'static str {
+    match self {
+      ActionType::Create => "create",
+      ActionType::Update => "update",
+      ActionType::Destroy => "destroy",
+      ActionType::DestroyThenCreate => "replace",
+      ActionType::DuplicateThenRemove => "replace, create first",
+      ActionType::Read => "read",
+      ActionType::Move => "move",
+      ActionType::Import => "import",
+      ActionType::Forget => "forget",
+    }
+  }
+}
+
+impl fmt::Display for ActionType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ActionType::Create => write!(f, "  +"),
+      ActionType::Update => write!(f, "  ~"),
+      ActionType::Destroy => write!(f, "  -"),
+      ActionType::DestroyThenCreate => write!(f, "-/+"),
+      ActionType::DuplicateThenRemove => write!(f, "+/-"),
+      ActionType::Read => write!(f, " <="),
+      ActionType::Move => write!(f, "  m"),
+      ActionType::Import => write!(f, "  i"),
+      ActionType::Forget => write!(f, "  ."),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Action {
+  pub typ: ActionType,
+  /// Address, example: `module.abc.aws_iam_policy.name["key"]`
+  pub reference: String,
+  /// Resource type, example: `aws_iam_policy`
+  pub resource: String,
+  /// Resource name, example: `name`
+  pub name: String,
+  /// The diff as printed in the plan, including the header notes.
+  pub content: String,
+  /// Top-level attributes of the diff, nested blocks as their children.
+  pub attributes: Vec<Attribute>,
+  /// Previous address, for moved resources.
+  pub moved_from: Option<String>,
+  /// ID of the object being imported.
+  pub import_id: Option<String>,
+  /// Attributes that force replacement, nested ones as `block.attribute`.
+  pub forces_replacement: Vec<String>,
+}
+
+impl Action {
+  /// Module instances the resource is in, outermost first.
+  ///
+  /// example: `["module.abc", "module.def[\"key\"]"]` for
+  /// `module.abc.module.def["key"].aws_iam_policy.name`
+  pub fn module_path(&self) -> Vec<&str> {
+    let mut path = Vec::new();
+    let mut start = 0;
+    for end in module_ends(&self.reference) {
+      path.push(&self.reference[start..end]);
+      start = end + 1;
+    }
+    path
+  }
+
+  /// Address of the module instance, empty for the root module.
+  pub fn module(&self) -> &str {
+    let end = module_ends(&self.reference).last().copied().unwrap_or_default();
+    &self.reference[..end]
+  }
+}
+
+// where each `module.name[key]` step of an address ends, keys can contain
+// dots and brackets in quotes
+fn module_ends(reference: &str) -> Vec<usize> {
+  let dots: Vec<usize> = Unquoted::new(reference)
+    .filter(|(_, ch)| *ch == '.')
+    .map(|(idx, _)| idx)
+    .collect();
+
+  // each step is two dot separated parts, the resource itself follows
+  let mut ends = Vec::new();
+  let mut start = 0;
+  for step in dots.chunks_exact(2) {
+    if !reference[start..].starts_with("module.") {
+      break;
+    }
+    ends.push(step[1]);
+    start = step[1] + 1;
+  }
+
+  ends
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeChange {
+  Unchanged,
+  Add,
+  Remove,
+  Update,
+  Replace,
+}
+
+impl fmt::Display for AttributeChange {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AttributeChange::Unchanged => write!(f, " "),
+      AttributeChange::Add => write!(f, "+"),
+      AttributeChange::Remove => write!(f, "-"),
+      AttributeChange::Update => write!(f, "~"),
+      AttributeChange::Replace => write!(f, "-/+"),
+    }
+  }
+}
+
+/// An attribute, nested block or collection element of a resource.
+///
+/// Values are kept as rendered by Terraform, e.g. `"quoted"` strings,
+/// `(known after apply)` or `(sensitive value)`. Blocks and collections have
+/// `children` instead of values.
+#[derive(Debug, Clone)]
+pub struct Attribute {
+  /// Empty for list and set elements.
+  pub name: String,
+  /// example: root_block_device.volume_size
+  pub path: String,
+  pub change: AttributeChange,
+  pub old: Option<String>,
+  pub new: Option<String>,
+  pub sensitive: bool,
+  pub known_after_apply: bool,
+  pub forces_replacement: bool,
+  pub children: Vec<Attribute>,
+}
+
+impl Attribute {
+  pub(crate) fn new(name: &str, parent_path: &str, change: AttributeChange) -> Self {
+    let path = match (parent_path, name) {
+      ("", name) => name.to_string(),
+      (parent, "") => parent.to_string(),
+      (parent, name) => format!("{}.{}", parent, name),
+    };
+
+    Attribute {
+      name: name.to_string(),
+      path,
+      change,
+      old: None,
+      new: None,
+      sensitive: false,
+      known_after_apply: false,
+      forces_replacement: false,
+      children: Vec::new(),
+    }
+  }
+
+  /// Paths of this and all nested attributes that force replacement.
+  pub fn forces_replacement_paths(&self) -> Vec<String> {
+    let mut paths = Vec::new();
+    if self.forces_replacement {
+      paths.push(self.path.clone());
+    }
+
+    for child in &self.children {
+      paths.extend(child.forces_replacement_paths());
+    }
+
+    paths
+  }
+}
+
+/// Error at a specific place of a plan.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+  /// 1-based, 0 if unknown, e.g. for JSON plans.
+  pub line: usize,
+  /// 1-based, 0 if unknown.
+  pub column: usize,
+  /// The offending line, or the resource address for JSON plans.
+  pub text: String,
+  pub message: String,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.line == 0 && self.text.is_empty() {
+      return write!(f, "{}", self.message);
+    }
+    if self.line == 0 {
+      return write!(f, "{}: {}", self.message, self.text);
+    }
+
+    writeln!(f, "line {}, column {}: {}", self.line, self.column, self.message)?;
+    writeln!(f, "  {}", self.text)?;
+    write!(f, "  {:>width$}", "^", width = self.column.max(1))
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Counts from the "Plan: N to add, N to change, N to destroy." line, with
+/// the optional "N to import" and "N to forget" of newer versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+  pub import: usize,
+  pub add: usize,
+  pub change: usize,
+  pub destroy: usize,
+  pub forget: usize,
+}
+
+impl Summary {
+  /// Counts the same way Terraform does for its summary.
+  pub fn count<'a>(actions: impl IntoIterator<Item = &'a Action>) -> Self {
+    let mut summary = Summary::default();
+
+    for act in actions {
+      if act.import_id.is_some() || act.typ == ActionType::Import {
+        summary.import += 1;
+      }
+
+      match act.typ {
+        ActionType::Create => summary.add += 1,
+        ActionType::Update => summary.change += 1,
+        ActionType::Destroy => summary.destroy += 1,
+        ActionType::DestroyThenCreate | ActionType::DuplicateThenRemove => {
+          summary.add += 1;
+          summary.destroy += 1;
+        }
+        ActionType::Forget => summary.forget += 1,
+        ActionType::Read | ActionType::Move | ActionType::Import => {}
+      }
+    }
+
+    summary
+  }
+}
+
+impl fmt::Display for Summary {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Plan: ")?;
+    if self.import > 0 {
+      write!(f, "{} to import, ", self.import)?;
+    }
+    write!(
+      f,
+      "{} to add, {} to change, {} to destroy",
+      self.add, self.change, self.destroy
+    )?;
+    if self.forget > 0 {
+      write!(f, ", {} to forget", self.forget)?;
+    }
+    write!(f, ".")
+  }
+}
+
+/// Change to an output value, only `Create`, `Update` and `Destroy` apply.
+#[derive(Debug, Clone)]
+pub struct Output {
+  pub typ: ActionType,
+  pub name: String,
+  pub content: String,
+  pub sensitive: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+  pub actions: Vec<Action>,
+  /// Not available for JSON plans.
+  pub summary: Option<Summary>,
+  /// Objects changed outside of Terraform.
+  pub drift: Vec<Action>,
+  pub outputs: Vec<Output>,
+  /// Actions skipped when parsing leniently.
+  pub warnings: Vec<ParseError>,
+}
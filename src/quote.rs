@@ -0,0 +1,41 @@
+use std::str::CharIndices;
+
+/// Characters outside of double quoted strings, with their byte offsets.
+/// Quotes themselves aren't returned and `\"` doesn't end a string.
+pub struct Unquoted<'a> {
+  chars: CharIndices<'a>,
+  in_quote: bool,
+}
+
+impl<'a> Unquoted<'a> {
+  pub fn new(text: &'a str) -> Self {
+    Unquoted {
+      chars: text.char_indices(),
+      in_quote: false,
+    }
+  }
+
+  /// Whether the last character read was in a string, i.e. at the end of the
+  /// text a quote is missing.
+  pub fn in_quote(&self) -> bool {
+    self.in_quote
+  }
+}
+
+impl Iterator for Unquoted<'_> {
+  type Item = (usize, char);
+
+  fn next(&mut self) -> Option<(usize, char)> {
+    loop {
+      let (idx, ch) = self.chars.next()?;
+      match ch {
+        '\\' if self.in_quote => {
+          self.chars.next();
+        }
+        '"' => self.in_quote = !self.in_quote,
+        _ if self.in_quote => {}
+        _ => return Some((idx, ch)),
+      }
+    }
+  }
+}
@@ -0,0 +1,319 @@
+use std::fmt::Write;
+use vp::{Action, ActionType, Plan, Summary};
+
+/// The summary from the plan, checked against what was actually parsed.
+pub fn totals(plan: &Plan) -> String {
+  let parsed = Summary::count(&plan.actions);
+
+  match &plan.summary {
+    Some(summary) if *summary == parsed => summary.to_string(),
+    Some(summary) => format!(
+      "{}\nMISMATCH, parsed {}\nsome resources may be missing, please check the plan output",
+      summary,
+      parsed.to_string().trim_start_matches("Plan: ")
+    ),
+    // e.g. JSON plans
+    None => parsed.to_string(),
+  }
+}
+
+/// Whether there are no resource changes, also according to the summary line
+/// of the plan, so Actions that couldn't be parsed aren't taken for none.
+pub fn no_changes(plan: &Plan) -> bool {
+  plan.actions.is_empty()
+    && plan
+      .summary
+      .as_ref()
+      .is_none_or(|summary| *summary == Summary::default())
+}
+
+/// One line per action, grouped by type, followed by the totals.
+pub fn summary(plan: &Plan) -> String {
+  let mut text = String::new();
+
+  let mut actions: Vec<_> = plan.actions.iter().collect();
+  // stable, so in plan order within each type
+  actions.sort_by_key(|act| &act.typ);
+
+  let width = actions.iter().map(|act| act.typ.name().len()).max().unwrap_or_default();
+  for act in actions {
+    let _ = write!(
+      text,
+      "{} {:width$} {}",
+      act.typ,
+      act.typ.name(),
+      act.reference,
+      width = width
+    );
+    if let Some(from) = &act.moved_from {
+      let _ = write!(text, " (from {})", from);
+    }
+    if !act.forces_replacement.is_empty() {
+      let _ = write!(text, " (forced by {})", act.forces_replacement.join(", "));
+    }
+    text.push('\n');
+  }
+
+  if no_changes(plan) {
+    text.push_str("No changes to resources.\n");
+  } else if !plan.actions.is_empty() {
+    text.push('\n');
+  }
+  text.push_str(&totals(plan));
+  text.push('\n');
+  for line in other_changes(plan) {
+    let _ = writeln!(text, "{}", line);
+  }
+
+  text
+}
+
+// changes not listed in the reports
+fn other_changes(plan: &Plan) -> Vec<String> {
+  let mut lines = Vec::new();
+  if !plan.drift.is_empty() {
+    lines.push(format!("{} object(s) changed outside of Terraform.", plan.drift.len()));
+  }
+  if !plan.outputs.is_empty() {
+    lines.push(format!("{} output(s) will change.", plan.outputs.len()));
+  }
+  lines
+}
+
+/// Default size limit of the Markdown report in bytes, GitHub comments are
+/// limited to 65536 characters.
+pub const MAX_SIZE: usize = 65_000;
+
+/// Smallest size limit of the Markdown report, enough for the totals and the
+/// table, which are always included.
+pub const MIN_SIZE: usize = 1_000;
+
+// room for the note about omitted actions
+const NOTE_SIZE: usize = 200;
+
+/// Markdown report for pull request comments: a table of the number of
+/// actions of each type, then the diffs in collapsible sections per module.
+/// Actions that don't fit in `max_size` bytes, at least `MIN_SIZE`, are left
+/// out with a note.
+pub fn markdown(plan: &Plan, max_size: usize) -> String {
+  let mut text = String::from("## Terraform plan\n\n");
+
+  for line in totals(plan).lines().map(String::from).chain(other_changes(plan)) {
+    let _ = writeln!(text, "{}\n", line);
+  }
+
+  if plan.actions.is_empty() {
+    if no_changes(plan) {
+      text.push_str("No changes to resources.\n");
+    }
+    return text;
+  }
+
+  let mut types: Vec<&ActionType> = plan.actions.iter().map(|act| &act.typ).collect();
+  types.sort();
+  types.dedup();
+
+  text.push_str("| Action | Resources |\n| --- | ---: |\n");
+  for typ in types {
+    let count = plan.actions.iter().filter(|act| act.typ == *typ).count();
+    let _ = writeln!(
+      text,
+      "| `{}` {} | {} |",
+      typ.to_string().trim_start(),
+      typ.name(),
+      count
+    );
+  }
+  text.push('\n');
+
+  // modules in the order they first appear
+  let mut modules: Vec<(&str, Vec<&Action>)> = Vec::new();
+  for act in &plan.actions {
+    match modules.iter_mut().find(|(module, _)| *module == act.module()) {
+      Some((_, actions)) => actions.push(act),
+      None => modules.push((act.module(), vec![act])),
+    }
+  }
+
+  // once an action doesn't fit, all following ones are left out too
+  let mut omitted = 0;
+  for (module, actions) in modules {
+    if omitted > 0 {
+      omitted += actions.len();
+      continue;
+    }
+
+    let summary = Summary::count(actions.iter().copied());
+    let name = match module {
+      "" => "root module".to_string(),
+      module => format!("<code>{}</code>", escape_html(module)),
+    };
+    let open = format!(
+      "<details><summary>{} (+{} ~{} -{})</summary>\n\n",
+      name, summary.add, summary.change, summary.destroy
+    );
+    let close = "</details>\n\n";
+
+    let mut body = String::new();
+    let mut included = 0;
+    for act in &actions {
+      let block = diff_block(act);
+      if text.len() + open.len() + body.len() + block.len() + close.len() + NOTE_SIZE > max_size {
+        break;
+      }
+      body.push_str(&block);
+      included += 1;
+    }
+
+    if included > 0 {
+      text.push_str(&open);
+      text.push_str(&body);
+      text.push_str(close);
+    }
+    omitted += actions.len() - included;
+  }
+
+  if omitted > 0 {
+    let _ = writeln!(
+      text,
+      "_{} of {} actions omitted due to the size limit of {} bytes._",
+      omitted,
+      plan.actions.len(),
+      max_size
+    );
+  }
+
+  text
+}
+
+// the content in a fenced `diff` block, with the markers moved to the start
+// of the lines so they're highlighted
+fn diff_block(act: &Action) -> String {
+  let mut diff = format!("# {} ({})\n", act.reference, act.typ.name());
+  // the content usually has a `# (moved from ...)` note already
+  if let Some(from) = &act.moved_from {
+    if !act.content.contains(&format!("(moved from {})", from)) {
+      let _ = writeln!(diff, "# moved from {}", from);
+    }
+  }
+  if !act.forces_replacement.is_empty() {
+    let _ = writeln!(diff, "# replacement forced by: {}", act.forces_replacement.join(", "));
+  }
+  for line in act.content.lines() {
+    diff.push_str(&diff_line(line));
+    diff.push('\n');
+  }
+
+  // longer than any backticks in the content
+  let longest = diff.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+  let fence = "`".repeat(longest.max(2) + 1);
+
+  format!("{}diff\n{}{}\n\n", fence, diff, fence)
+}
+
+// example: `      ~ name = "a" -> "b"` -> `!       name = "a" -> "b"`
+fn diff_line(line: &str) -> String {
+  let rest = line.trim_start();
+  let indent = &line[..line.len() - rest.len()];
+
+  let (marker, diff_marker) = if rest.starts_with("-/+") || rest.starts_with("+/-") {
+    (3, "!")
+  } else if rest.starts_with('+') {
+    (1, "+")
+  } else if rest.starts_with('-') {
+    (1, "-")
+  } else if rest.starts_with('~') {
+    (1, "!")
+  } else {
+    return line.to_string();
+  };
+
+  format!("{:marker$}{}{}", diff_marker, indent, &rest[marker..], marker = marker)
+}
+
+fn escape_html(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PLAN: &str = r#"Terraform will perform the following actions:
+
+  # aws_instance.a must be replaced
+-/+ resource "aws_instance" "a" {
+      ~ ami = "a" -> "b" # forces replacement
+    }
+
+  # aws_s3_bucket.c will be updated in-place
+  # (moved from aws_s3_bucket.old_c)
+  ~ resource "aws_s3_bucket" "c" {
+      ~ tags = {}
+    }
+
+  # aws_iam_role.b will be created
+  + resource "aws_iam_role" "b" {
+      + name = "b"
+    }
+
+Plan: 2 to add, 1 to change, 1 to destroy.
+"#;
+
+  #[test]
+  fn summary_by_type() {
+    let plan = vp::parse_text_plan(PLAN.as_bytes()).unwrap();
+    assert_eq!(
+      summary(&plan),
+      "  + create  aws_iam_role.b
+  ~ update  aws_s3_bucket.c (from aws_s3_bucket.old_c)
+-/+ replace aws_instance.a (forced by ami)
+
+Plan: 2 to add, 1 to change, 1 to destroy.
+"
+    );
+  }
+
+  #[test]
+  fn summary_mismatch() {
+    let mut plan = vp::parse_text_plan(PLAN.as_bytes()).unwrap();
+    plan.actions.clear();
+
+    let text = summary(&plan);
+    assert!(!text.contains("No changes"));
+    assert!(text.contains("MISMATCH, parsed 0 to add, 0 to change, 0 to destroy."));
+  }
+
+  #[test]
+  fn markdown_moved_note_once() {
+    let plan = vp::parse_text_plan(PLAN.as_bytes()).unwrap();
+    let text = markdown(&plan, MAX_SIZE);
+    assert_eq!(text.matches("moved from aws_s3_bucket.old_c").count(), 1);
+    assert!(text.contains("# aws_instance.a (replace)\n# replacement forced by: ami\n"));
+  }
+
+  #[test]
+  fn markdown_truncated() {
+    let mut plan_text = String::from("Terraform will perform the following actions:\n\n");
+    for idx in 0..50 {
+      plan_text.push_str(&format!(
+        "  # aws_iam_role.r{0} will be created\n  + resource \"aws_iam_role\" \"r{0}\" {{\n      + name = \"r{0}\"\n    }}\n\n",
+        idx
+      ));
+    }
+    plan_text.push_str("Plan: 50 to add, 0 to change, 0 to destroy.\n");
+    let plan = vp::parse_text_plan(plan_text.as_bytes()).unwrap();
+
+    let text = markdown(&plan, MIN_SIZE);
+    assert!(text.len() <= MIN_SIZE, "{} bytes", text.len());
+    assert!(text.contains("| `+` create | 50 |"));
+
+    let shown = text.matches("(create)\n").count();
+    assert!(shown > 0);
+    assert!(text.ends_with(&format!(
+      "_{} of 50 actions omitted due to the size limit of {} bytes._\n",
+      50 - shown,
+      MIN_SIZE
+    )));
+  }
+}
@@ -0,0 +1,77 @@
+use std::{
+  collections::HashSet,
+  env, fs,
+  io::{self, Write},
+  path::PathBuf,
+};
+use vp::Action;
+
+/// Addresses of the reviewed actions, saved in the state directory under a
+/// hash of the plan so reopening the same plan restores them.
+#[derive(Debug, Default)]
+pub struct Review {
+  // `None` without a state directory
+  path: Option<PathBuf>,
+  pub reviewed: HashSet<String>,
+}
+
+impl Review {
+  /// Previous progress on the plan, nothing if there's none or it can't be
+  /// read.
+  pub fn load(actions: &[Action]) -> Self {
+    let path = state_dir().map(|dir| dir.join(format!("{:016x}", plan_hash(actions))));
+
+    let reviewed = path
+      .as_ref()
+      .and_then(|path| fs::read_to_string(path).ok())
+      .map(|text| text.lines().map(String::from).collect())
+      .unwrap_or_default();
+
+    Review { path, reviewed }
+  }
+
+  pub fn save(&self) -> io::Result<()> {
+    let path = match &self.path {
+      Some(path) => path,
+      None => return Ok(()),
+    };
+
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    let mut reviewed: Vec<&String> = self.reviewed.iter().collect();
+    reviewed.sort();
+
+    let mut file = fs::File::create(path)?;
+    for reference in reviewed {
+      writeln!(file, "{}", reference)?;
+    }
+    Ok(())
+  }
+}
+
+// $XDG_STATE_HOME/vp, defaulting to ~/.local/state/vp
+fn state_dir() -> Option<PathBuf> {
+  let state = env::var_os("XDG_STATE_HOME")
+    .filter(|dir| !dir.is_empty())
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+
+  Some(state.join("vp"))
+}
+
+// FNV-1a of the addresses and diffs, stable across builds unlike `std`'s
+// hasher, and the same whether the plan was colored or not
+fn plan_hash(actions: &[Action]) -> u64 {
+  let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+  for act in actions {
+    for text in [&act.reference, &act.content] {
+      for byte in text.bytes().chain([0]) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+      }
+    }
+  }
+  hash
+}
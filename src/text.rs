@@ -0,0 +1,924 @@
+use crate::{ansi, quote::Unquoted, Action, ActionType, Attribute, AttributeChange, Output, ParseError, Plan, Summary};
+use anyhow::{bail, Context, Result};
+use std::{
+  fmt,
+  io::{self, BufRead},
+};
+
+pub fn read_plan(reader: impl BufRead, lenient: bool) -> Result<Plan> {
+  let mut input = Input {
+    reader,
+    line_number: 0,
+    last_line: String::new(),
+    lenient,
+    warnings: Vec::new(),
+  };
+
+  let mut plan = read(&mut input).map_err(|err| input.wrap(err))?;
+  plan.warnings = input.warnings;
+
+  Ok(plan)
+}
+
+// a line based reader, which keeps track of where it is for errors
+struct Input<R> {
+  reader: R,
+  line_number: usize,
+  last_line: String,
+  // skip Actions that can't be parsed, see `warnings`
+  lenient: bool,
+  warnings: Vec<ParseError>,
+}
+
+impl<R: BufRead> Input<R> {
+  // colored and plain plans are parsed the same
+  fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+    let len = self.reader.read_line(line)?;
+    ansi::strip(line);
+
+    if len > 0 {
+      self.line_number += 1;
+      self.last_line.clear();
+      self.last_line.push_str(line.trim_end());
+    }
+
+    Ok(len)
+  }
+
+  // error at the given 1-based column of the last line
+  fn error(&self, column: usize, message: impl fmt::Display) -> anyhow::Error {
+    ParseError {
+      line: self.line_number,
+      column,
+      text: self.last_line.clone(),
+      message: message.to_string(),
+    }
+    .into()
+  }
+
+  // adds the position to errors that don't have one yet
+  fn wrap(&self, err: anyhow::Error) -> ParseError {
+    match err.downcast::<ParseError>() {
+      Ok(err) => err,
+      Err(err) => ParseError {
+        line: self.line_number,
+        column: self.last_line.len() - self.last_line.trim_start().len() + 1,
+        text: self.last_line.clone(),
+        message: format!("{:#}", err),
+      },
+    }
+  }
+}
+
+// column of `rest`, which has to be a part of `line`, in characters like the
+// caret of `ParseError` is placed
+fn column(line: &str, rest: &str) -> usize {
+  let offset = rest.as_ptr() as usize - line.as_ptr() as usize;
+  line[..offset].chars().count() + 1
+}
+
+// column of the last character of `rest`
+fn last_column(line: &str, rest: &str) -> usize {
+  column(line, rest) + rest.chars().count().saturating_sub(1)
+}
+
+fn read(input: &mut Input<impl BufRead>) -> Result<Plan> {
+  let mut plan = Plan::default();
+  let mut line = String::new();
+
+  // skip to actions start or no changes
+  while input.read_line(&mut line)? > 0 {
+    if line.starts_with("Terraform will perform the following actions:") {
+      break;
+    }
+
+    if line.starts_with("Note: Objects have changed outside of Terraform") && skip_to_header(input, &mut line)? {
+      read_actions(input, &mut line, &mut plan.drift).context("expect drifted Actions")?;
+    }
+
+    // example: No changes. Infrastructure is up-to-date.
+    // example: No changes. Your infrastructure matches the configuration.
+    if line.starts_with("No changes.") {
+      return Ok(plan);
+    }
+
+    // only outputs changed
+    if line.starts_with("Changes to Outputs:") {
+      read_outputs(input, &mut line, &mut plan.outputs).context("expect Outputs")?;
+      return Ok(plan);
+    }
+    line.clear();
+  }
+
+  // reached the end without finding any of the above
+  if line.is_empty() {
+    bail!("expecting Terraform plan output");
+  }
+
+  if skip_to_header(input, &mut line)? {
+    read_actions(input, &mut line, &mut plan.actions)?;
+  }
+
+  loop {
+    if line.starts_with("Plan: ") {
+      plan.summary = Some(read_summary(&line).context("expecting Plan summary")?);
+    }
+
+    if line.starts_with("Changes to Outputs:") {
+      read_outputs(input, &mut line, &mut plan.outputs).context("expect Outputs")?;
+      break;
+    }
+
+    line.clear();
+    if input.read_line(&mut line)? == 0 {
+      break;
+    }
+  }
+
+  Ok(plan)
+}
+
+// example:
+//   ~ name = "old" -> "new"
+//   + list = [
+//       + "a",
+//     ]
+fn read_outputs(input: &mut Input<impl BufRead>, line: &mut String, outputs: &mut Vec<Output>) -> Result<()> {
+  loop {
+    line.clear();
+    if input.read_line(line)? == 0 {
+      break;
+    }
+
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    if !line.starts_with(' ') {
+      break;
+    }
+
+    let typ = match line.get(..4) {
+      Some("  + ") => Some(ActionType::Create),
+      Some("  ~ ") => Some(ActionType::Update),
+      Some("  - ") => Some(ActionType::Destroy),
+      _ => None,
+    };
+
+    match (typ, outputs.last_mut()) {
+      (Some(typ), _) => {
+        let (name, value) = line[4..].split_once('=').context("expecting '='")?;
+        outputs.push(Output {
+          typ,
+          name: name.trim().to_string(),
+          sensitive: value.contains("(sensitive value)") || value.contains("(sensitive)"),
+          content: line.clone(),
+        });
+      }
+      // rest of a multi-line value
+      (None, Some(output)) => output.content.push_str(line),
+      (None, None) => bail!("unexpected Output: {}", line),
+    }
+  }
+
+  Ok(())
+}
+
+// returns whether a header line `  # ...` was found, which is left in `line`,
+// stops early at the sections following the actions
+fn skip_to_header(input: &mut Input<impl BufRead>, line: &mut String) -> Result<bool> {
+  line.clear();
+  while input.read_line(line)? > 0 {
+    if line.starts_with("  # ") {
+      return Ok(true);
+    }
+
+    if line.starts_with("Plan: ") || line.starts_with("Changes to Outputs:") {
+      return Ok(false);
+    }
+    line.clear();
+  }
+
+  Ok(false)
+}
+
+// reads Actions starting from the header in `line`, until the first line that
+// doesn't belong to any Action (e.g. `Plan: `), which is left in `line`
+fn read_actions(input: &mut Input<impl BufRead>, line: &mut String, actions: &mut Vec<Action>) -> Result<()> {
+  loop {
+    if line.starts_with("  # ") {
+      let header_line = input.line_number;
+      match read_action(input, line) {
+        Ok(action) => actions.push(action),
+        Err(err) if input.lenient => {
+          input.warnings.push(input.wrap(err));
+          // continue with the next header, which might be in `line` already
+          // unless it's the header that failed
+          if input.line_number == header_line {
+            line.clear();
+            if input.read_line(line)? == 0 {
+              return Ok(());
+            }
+          }
+          while !line.starts_with("  # ") && !is_section(line) {
+            line.clear();
+            if input.read_line(line)? == 0 {
+              return Ok(());
+            }
+          }
+          continue;
+        }
+        Err(err) => return Err(err),
+      }
+    } else if !line.starts_with(' ') && !line.trim().is_empty() {
+      break;
+    }
+
+    line.clear();
+
+    if input.read_line(line)? == 0 {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+// lines starting the sections around the actions, where skipping an Action
+// that can't be read stops
+fn is_section(line: &str) -> bool {
+  [
+    "Plan: ",
+    "Changes to Outputs:",
+    "Unless you have made",
+    "No changes.",
+    "Terraform will perform",
+  ]
+  .iter()
+  .any(|start| line.starts_with(start))
+}
+
+// reads an Action from its header in `line` to its closing brace, if that's
+// missing the line that can't be part of it is left in `line`
+fn read_action(input: &mut Input<impl BufRead>, line: &mut String) -> Result<Action> {
+  let mut action = read_action_header(input, line)?;
+  let mut scanner = BlockScanner::default();
+  let mut body = String::new();
+
+  // the line with the resource and opening brace
+  action.content.push_str(line);
+  scanner.scan(line);
+
+  while scanner.depth > 0 {
+    line.clear();
+    if input.read_line(line)? == 0 {
+      bail!("expecting end of Action {}", action.reference);
+    }
+
+    // example: `Plan: `, `Changes to Outputs:` or the next header, but heredocs
+    // can contain anything
+    let outside = !line.starts_with(' ') && !line.trim().is_empty();
+    if scanner.heredoc.is_none() && (outside || line.starts_with("  # ")) {
+      return Err(input.error(1, format!("expecting end of Action {}", action.reference)));
+    }
+
+    action.content.push_str(line);
+    scanner.scan(line);
+
+    if scanner.depth > 0 {
+      body.push_str(line);
+    }
+  }
+
+  action.attributes = read_attributes(&body);
+  action.forces_replacement = action
+    .attributes
+    .iter()
+    .flat_map(Attribute::forces_replacement_paths)
+    .collect();
+
+  Ok(action)
+}
+
+// tracks the nesting of braces, brackets and parens, ignoring those in
+// strings, heredocs and `#` comments
+#[derive(Debug, Default)]
+struct BlockScanner {
+  depth: usize,
+  // closing tag of the heredoc we're in
+  heredoc: Option<String>,
+}
+
+impl BlockScanner {
+  fn scan(&mut self, line: &str) {
+    let text = line.trim();
+
+    if let Some(tag) = &self.heredoc {
+      if heredoc_ends(text, tag) {
+        self.heredoc = None;
+      }
+      return;
+    }
+
+    for (idx, ch) in Unquoted::new(text) {
+      match ch {
+        '#' => break,
+        '{' | '[' | '(' => self.depth += 1,
+        '}' | ']' | ')' => self.depth = self.depth.saturating_sub(1),
+        '<' if text[idx..].starts_with("<<") => {
+          self.heredoc = heredoc_tag(&text[idx..]).map(String::from);
+          break;
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+// example: Plan: 1 to add, 2 to change, 0 to destroy.
+fn read_summary(line: &str) -> Result<Summary> {
+  let mut summary = Summary::default();
+
+  let rest = line.trim().strip_prefix("Plan: ").context("expecting 'Plan: '")?;
+  let rest = rest.strip_suffix('.').unwrap_or(rest);
+
+  for part in rest.split(", ") {
+    let (count, what) = part.split_once(" to ").context("expecting 'N to ...'")?;
+    let count = count.parse().context("expecting count")?;
+
+    match what {
+      "import" => summary.import = count,
+      "add" => summary.add = count,
+      "change" => summary.change = count,
+      "destroy" => summary.destroy = count,
+      "forget" => summary.forget = count,
+      _ => bail!("unexpected Plan summary: {}", what),
+    }
+  }
+
+  Ok(summary)
+}
+
+fn read_action_header(input: &mut Input<impl BufRead>, line: &mut String) -> Result<Action> {
+  let header = line.trim_start().strip_prefix("# ").context("expecting '# '")?;
+  let (reference, rest) = split_reference(header).context("expecting resource reference")?;
+  let mut reference = reference.to_string();
+  let importing = rest.trim() == "will be imported";
+
+  let mut content = String::new();
+  let mut moved_from = None;
+  let mut import_id = None;
+
+  // example: `  # aws_iam_policy.old has moved to aws_iam_policy.new`
+  if let Some(to) = rest.trim().strip_prefix("has moved to ") {
+    let (to, _) = split_reference(to).context("expecting new resource reference")?;
+    content.push_str(&format!("  # (moved from {})\n", reference));
+    moved_from = Some(reference);
+    reference = to.to_string();
+  }
+
+  // notes like `  # (config refers to values not yet known)` follow the
+  // header, keep them as part of the content
+  loop {
+    line.clear();
+    input.read_line(line).context("expecting Action detail")?;
+
+    let note = match line.strip_prefix("  # (") {
+      Some(note) => note.trim_end().trim_end_matches(')'),
+      None => break,
+    };
+
+    if let Some(from) = note.strip_prefix("moved from ") {
+      moved_from = Some(from.to_string());
+    } else if let Some(id) = note.strip_prefix("imported from ") {
+      import_id = Some(id.trim_matches('"').to_string());
+    }
+
+    content.push_str(line);
+  }
+
+  let (typ_text, rest) = line
+    .split_at_checked(4)
+    .ok_or_else(|| input.error(1, "expecting Action type"))?;
+
+  let typ = match typ_text {
+    "  + " => ActionType::Create,
+    "  ~ " => ActionType::Update,
+    "  - " => ActionType::Destroy,
+    "-/+ " => ActionType::DestroyThenCreate,
+    "+/- " => ActionType::DuplicateThenRemove,
+    " <= " => ActionType::Read,
+    "  . " | " .  " => ActionType::Forget,
+    "    " if moved_from.is_some() => ActionType::Move,
+    "    " if importing => ActionType::Import,
+    _ => {
+      let column = typ_text.len() - typ_text.trim_start().len() + 1;
+      return Err(input.error(column, format!("unexpected Action type: {}", typ_text.trim())));
+    }
+  };
+
+  // example: resource "aws_iam_policy" "name" {
+  // example: data "aws_iam_policy_document" "name"  {
+  let rest = rest.trim();
+  let (mode, rest) = rest
+    .split_once(' ')
+    .ok_or_else(|| input.error(column(line, rest), "expecting resource mode"))?;
+  if mode != "resource" && mode != "data" {
+    return Err(input.error(column(line, mode), format!("unexpected resource mode: {}", mode)));
+  }
+
+  let rest = rest
+    .strip_suffix('{')
+    .ok_or_else(|| input.error(last_column(line, rest), "expecting opening brace"))?
+    .trim_end();
+  let rest = rest
+    .strip_prefix('"')
+    .ok_or_else(|| input.error(column(line, rest), "expecting beginning quote"))?;
+  let rest = rest
+    .strip_suffix('"')
+    .ok_or_else(|| input.error(last_column(line, rest), "expecting ending quote"))?;
+  let mut parts = rest.split("\" \"");
+  let resource = parts.next().unwrap_or_default().to_string();
+  let name = parts
+    .next()
+    .ok_or_else(|| input.error(column(line, rest), "expecting name"))?
+    .to_string();
+
+  Ok(Action {
+    typ,
+    reference,
+    resource,
+    name,
+    content,
+    attributes: Vec::new(),
+    moved_from,
+    import_id,
+    forces_replacement: Vec::new(),
+  })
+}
+
+// builds the attribute tree from the content of an Action, example:
+//       ~ engine = "mysql" -> "postgres" # forces replacement
+//       ~ root_block_device {
+//           ~ volume_size = 8 -> 10 # forces replacement
+//         }
+//       + tags = {
+//           + "Name" = "web"
+//         }
+fn read_attributes(content: &str) -> Vec<Attribute> {
+  // the bottom is a placeholder for the resource itself
+  let mut stack = vec![Attribute::new("", "", AttributeChange::Unchanged)];
+  let mut lines = content.lines();
+
+  while let Some(line) = lines.next() {
+    let text = line.trim();
+
+    // notes like `# (3 unchanged attributes hidden)`
+    if text.is_empty() || text.starts_with('#') {
+      continue;
+    }
+
+    let (text, forces) = match text.strip_suffix("# forces replacement") {
+      Some(text) => (text.trim_end(), true),
+      None => (text, false),
+    };
+
+    // example: `}`, `],`, `) -> null`
+    if text.starts_with(['}', ']', ')']) {
+      if stack.len() > 1 {
+        let done = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(done);
+      }
+      continue;
+    }
+
+    let (change, text) = match text.split_once(' ') {
+      Some(("+", rest)) => (AttributeChange::Add, rest),
+      Some(("-", rest)) => (AttributeChange::Remove, rest),
+      Some(("~", rest)) => (AttributeChange::Update, rest),
+      Some(("-/+", rest)) | Some(("+/-", rest)) => (AttributeChange::Replace, rest),
+      _ => (AttributeChange::Unchanged, text),
+    };
+    let text = text.trim_start();
+
+    let (name, value) = match find_unquoted(text, " = ") {
+      Some(idx) => (&text[..idx], Some(text[idx + 3..].trim())),
+      // nested block, example: `root_block_device {`
+      None if text.ends_with(" {") => (text.trim_end_matches(" {"), None),
+      // list or set element
+      None => ("", Some(text)),
+    };
+
+    let parent = stack.last().unwrap();
+    let mut attr = Attribute::new(name.trim().trim_matches('"'), &parent.path, change);
+    attr.forces_replacement = forces;
+
+    let value = match value {
+      Some(value) => value.trim_end_matches(','),
+      None => {
+        stack.push(attr);
+        continue;
+      }
+    };
+
+    // example: `{`, `[`, `jsonencode(`
+    if value.ends_with(['{', '[', '(']) {
+      stack.push(attr);
+      continue;
+    }
+
+    // example: `<<-EOT`, in updates each line of the body has its own marker
+    // at the same column as nested attributes
+    if let Some(tag) = heredoc_tag(value) {
+      let marker_col = line.len() - line.trim_start().len() + 4;
+      let mut old = Vec::new();
+      let mut new = Vec::new();
+
+      for line in lines.by_ref() {
+        if heredoc_ends(line, tag) {
+          break;
+        }
+
+        let indented = line.get(..marker_col).is_some_and(|indent| indent.trim().is_empty());
+        let (marker, body) = match (line.get(marker_col..marker_col + 2), line.get(marker_col + 2..)) {
+          (Some(marker), Some(body)) if indented => (marker, body),
+          _ => ("", line.trim_start()),
+        };
+        match marker {
+          "- " => old.push(body),
+          "+ " => new.push(body),
+          _ => {
+            old.push(body);
+            new.push(body);
+          }
+        }
+      }
+
+      if change != AttributeChange::Remove {
+        attr.new = Some(new.join("\n"));
+      }
+      if change != AttributeChange::Add {
+        attr.old = Some(old.join("\n"));
+      }
+
+      stack.last_mut().unwrap().children.push(attr);
+      continue;
+    }
+
+    let (old, new) = match find_unquoted(value, " -> ") {
+      Some(idx) => (Some(&value[..idx]), Some(&value[idx + 4..])),
+      None => (None, Some(value)),
+    };
+    let new = new.filter(|new| *new != "null");
+
+    match change {
+      AttributeChange::Add => attr.new = new.map(String::from),
+      AttributeChange::Remove => attr.old = old.or(new).map(String::from),
+      AttributeChange::Unchanged => {
+        attr.old = new.map(String::from);
+        attr.new = new.map(String::from);
+      }
+      AttributeChange::Update | AttributeChange::Replace => {
+        attr.old = old.map(String::from);
+        attr.new = new.map(String::from);
+      }
+    }
+
+    attr.known_after_apply = attr.new.as_deref() == Some("(known after apply)");
+    attr.sensitive = value.contains("(sensitive value)") || value.contains("(sensitive)");
+
+    stack.last_mut().unwrap().children.push(attr);
+  }
+
+  // unbalanced content, e.g. truncated plans
+  while stack.len() > 1 {
+    let done = stack.pop().unwrap();
+    stack.last_mut().unwrap().children.push(done);
+  }
+
+  stack.pop().unwrap().children
+}
+
+// closing tag of the heredoc `value` starts, example: `<<-EOT`, which has to
+// end the line except for a comment like `# forces replacement`
+fn heredoc_tag(value: &str) -> Option<&str> {
+  let tag = value.strip_prefix("<<")?.trim_start_matches('-');
+  let tag = tag.split_once('#').map_or(tag, |(tag, _)| tag).trim();
+
+  if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '_') {
+    return None;
+  }
+  Some(tag)
+}
+
+// example: `EOT`, or `EOT -> null` when the value is removed
+fn heredoc_ends(line: &str, tag: &str) -> bool {
+  let text = line.trim();
+  text == tag || text.strip_prefix(tag).is_some_and(|rest| rest.starts_with(" -> "))
+}
+
+// position of `pat` outside of any quoted string
+fn find_unquoted(text: &str, pat: &str) -> Option<usize> {
+  Unquoted::new(text)
+    .find(|(idx, _)| text[*idx..].starts_with(pat))
+    .map(|(idx, _)| idx)
+}
+
+// example: `module.abc.aws_iam_policy.name["my key"] will be created`
+// keys may contain whitespace and escaped quotes, so only stop at whitespace
+// outside of quotes
+fn split_reference(rest: &str) -> Result<(&str, &str)> {
+  let mut chars = Unquoted::new(rest);
+  let end = chars
+    .find(|(_, ch)| ch.is_whitespace())
+    .map_or(rest.len(), |(idx, _)| idx);
+
+  if chars.in_quote() {
+    bail!("expecting ending quote in key");
+  }
+
+  let (reference, rest) = rest.split_at(end);
+  if reference.is_empty() {
+    bail!("empty resource reference");
+  }
+
+  Ok((reference, rest))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(text: &str) -> Plan {
+    read_plan(text.as_bytes(), false).unwrap()
+  }
+
+  #[test]
+  fn split_reference_keys() {
+    let (reference, rest) = split_reference(r#"aws_iam_policy.name["my key"] will be created"#).unwrap();
+    assert_eq!(reference, r#"aws_iam_policy.name["my key"]"#);
+    assert_eq!(rest, " will be created");
+
+    let (reference, _) = split_reference(r#"module.a["say \"hi\" "].aws_iam_policy.name will be created"#).unwrap();
+    assert_eq!(reference, r#"module.a["say \"hi\" "].aws_iam_policy.name"#);
+
+    assert!(split_reference(r#"aws_iam_policy.name["my key will be created"#).is_err());
+    assert!(split_reference(" will be created").is_err());
+  }
+
+  #[test]
+  fn heredocs() {
+    let plan = parse(
+      r#"Terraform will perform the following actions:
+
+  # aws_instance.a must be replaced
+-/+ resource "aws_instance" "a" {
+      ~ user_data = <<-EOT # forces replacement
+          - echo {old
+          + echo [new
+        EOT
+        id        = "i-1"
+    }
+
+  # aws_instance.b will be updated in-place
+  ~ resource "aws_instance" "b" {
+      - policy = <<-EOT
+            {"a": [1,
+        EOT -> null
+        id     = "i-2"
+    }
+
+Plan: 1 to add, 1 to change, 1 to destroy.
+"#,
+    );
+
+    assert_eq!(plan.actions.len(), 2);
+    let user_data = &plan.actions[0].attributes[0];
+    assert_eq!(user_data.old.as_deref(), Some("echo {old"));
+    assert_eq!(user_data.new.as_deref(), Some("echo [new"));
+    assert_eq!(plan.actions[0].forces_replacement, ["user_data"]);
+
+    let attributes = &plan.actions[1].attributes;
+    assert_eq!(attributes.len(), 2);
+    assert_eq!(attributes[0].old.as_deref(), Some(r#"{"a": [1,"#));
+    assert_eq!(attributes[0].new, None);
+    assert_eq!(attributes[1].name, "id");
+  }
+
+  #[test]
+  fn summary_with_import_and_forget() {
+    let summary = read_summary("Plan: 1 to import, 2 to add, 3 to change, 4 to destroy, 5 to forget.\n").unwrap();
+    assert_eq!(
+      summary,
+      Summary {
+        import: 1,
+        add: 2,
+        change: 3,
+        destroy: 4,
+        forget: 5,
+      }
+    );
+  }
+
+  #[test]
+  fn drift_only() {
+    let plan = parse(
+      r#"Note: Objects have changed outside of Terraform
+
+Terraform detected the following changes made outside of Terraform since the
+last "terraform apply":
+
+  # aws_instance.x has been deleted
+  - resource "aws_instance" "x" {
+      - id = "i-1" -> null
+    }
+
+Unless you have made equivalent changes to your configuration, or ignored the
+relevant attributes using ignore_changes, the following plan may include
+actions to undo or respond to these changes.
+
+No changes. Your infrastructure matches the configuration.
+"#,
+    );
+
+    assert!(plan.actions.is_empty());
+    assert_eq!(plan.drift.len(), 1);
+    assert_eq!(plan.drift[0].typ, ActionType::Destroy);
+  }
+
+  #[test]
+  fn outputs_only() {
+    let plan = parse(
+      r#"Changes to Outputs:
+  + foo = "bar"
+  ~ list = [
+      + "a",
+    ]
+
+You can apply this plan to save these new output values to the Terraform
+state, without changing any real infrastructure.
+"#,
+    );
+
+    assert!(plan.actions.is_empty());
+    let names: Vec<&str> = plan.outputs.iter().map(|output| output.name.as_str()).collect();
+    assert_eq!(names, ["foo", "list"]);
+    assert!(plan.outputs[1].content.contains("+ \"a\""));
+  }
+
+  #[test]
+  fn lenient_keeps_later_actions() {
+    let plan = read_plan(
+      r#"Terraform will perform the following actions:
+
+  # aws_instance.a will be created
+  + resource "aws_instance" "a" {
+      + id = (known after apply)
+    }
+
+  # aws_instance.b must be replaced
+-/+ resource "aws_instance" "b"
+      ~ id = "i-1" -> (known after apply)
+    }
+
+  # aws_instance.c will be destroyed
+  - resource "aws_instance" "c" {
+      - id = "i-2" -> null
+    }
+
+Plan: 2 to add, 0 to change, 2 to destroy.
+"#
+      .as_bytes(),
+      true,
+    )
+    .unwrap();
+
+    let references: Vec<&str> = plan.actions.iter().map(|act| act.reference.as_str()).collect();
+    assert_eq!(references, ["aws_instance.a", "aws_instance.c"]);
+    assert_eq!(plan.warnings.len(), 1);
+    assert_eq!(plan.warnings[0].line, 9);
+    assert!(plan.summary.is_some());
+  }
+
+  #[test]
+  fn error_positions() {
+    let err = read_plan(
+      r#"Terraform will perform the following actions:
+
+  # aws_instance.é will be created
+  + resource "aws_instance" "é"
+    }
+"#
+      .as_bytes(),
+      false,
+    )
+    .unwrap_err();
+
+    let err = err.downcast::<ParseError>().unwrap();
+    assert_eq!((err.line, err.column), (4, 31));
+    assert_eq!(err.message, "expecting opening brace");
+    assert!(err.to_string().ends_with(&format!("\n  {:>31}", "^")));
+  }
+
+  #[test]
+  fn data_reads() {
+    let plan = parse(
+      r#"Terraform will perform the following actions:
+
+  # data.aws_iam_policy_document.x will be read during apply
+  # (config refers to values not yet known)
+ <= data "aws_iam_policy_document" "x"  {
+      + id   = (known after apply)
+    }
+
+Plan: 0 to add, 0 to change, 0 to destroy.
+"#,
+    );
+
+    let action = &plan.actions[0];
+    assert_eq!(action.typ, ActionType::Read);
+    assert_eq!(action.reference, "data.aws_iam_policy_document.x");
+    assert_eq!(
+      (action.resource.as_str(), action.name.as_str()),
+      ("aws_iam_policy_document", "x")
+    );
+    assert!(action
+      .content
+      .starts_with("  # (config refers to values not yet known)\n <= data"));
+    assert!(action.attributes[0].known_after_apply);
+  }
+
+  #[test]
+  fn moved_imported_and_forgotten() {
+    let plan = parse(
+      r#"Terraform will perform the following actions:
+
+  # aws_s3_bucket.a has moved to aws_s3_bucket.b
+    resource "aws_s3_bucket" "b" {
+        id = "bucket"
+    }
+
+  # aws_s3_bucket.c will be updated in-place
+  # (moved from aws_s3_bucket.old_c)
+  ~ resource "aws_s3_bucket" "c" {
+      ~ tags = {}
+    }
+
+  # aws_instance.example will be imported
+    resource "aws_instance" "example" {
+        ami = "ami-1"
+    }
+
+  # aws_instance.i2 will be updated in-place
+  # (imported from "i-123")
+  ~ resource "aws_instance" "i2" {
+      ~ ami = "a" -> "b"
+    }
+
+  # aws_instance.gone will no longer be managed by Terraform, but will not be destroyed
+  # (destroy = false is set in the configuration)
+ .  resource "aws_instance" "gone" {
+        id = "i-9"
+    }
+
+Plan: 2 to import, 0 to add, 2 to change, 0 to destroy, 1 to forget.
+"#,
+    );
+
+    let actions: Vec<(&ActionType, &str, Option<&str>, Option<&str>)> = plan
+      .actions
+      .iter()
+      .map(|act| {
+        (
+          &act.typ,
+          act.reference.as_str(),
+          act.moved_from.as_deref(),
+          act.import_id.as_deref(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      actions,
+      [
+        (&ActionType::Move, "aws_s3_bucket.b", Some("aws_s3_bucket.a"), None),
+        (
+          &ActionType::Update,
+          "aws_s3_bucket.c",
+          Some("aws_s3_bucket.old_c"),
+          None
+        ),
+        (&ActionType::Import, "aws_instance.example", None, None),
+        (&ActionType::Update, "aws_instance.i2", None, Some("i-123")),
+        (&ActionType::Forget, "aws_instance.gone", None, None),
+      ]
+    );
+
+    // the notes are part of the content, also the one for the header form
+    assert!(plan.actions[0]
+      .content
+      .starts_with("  # (moved from aws_s3_bucket.a)\n"));
+    assert!(plan.actions[3].content.starts_with("  # (imported from \"i-123\")\n"));
+    assert!(plan.actions[4].content.starts_with("  # (destroy = false"));
+    assert_eq!(plan.summary, Some(Summary::count(&plan.actions)));
+  }
+}
@@ -0,0 +1,1051 @@
+use crate::{clipboard, report, review::Review};
+use anyhow::{Context, Result};
+use cursive::{
+  backends,
+  event::{Event, Key},
+  theme::{BaseColor, Color, Effect, PaletteColor, Style},
+  traits::{Nameable, Resizable, Scrollable},
+  utils::markup::StyledString,
+  views::{Checkbox, Dialog, DummyView, EditView, LinearLayout, OnEventView, Panel, SelectView, TextView},
+  Cursive, View,
+};
+use regex::{Regex, RegexBuilder};
+use std::{
+  collections::HashSet,
+  env, fmt, fs,
+  hash::Hash,
+  io::{self, Write},
+};
+use vp::{Action, ActionType, Attribute, AttributeChange, Output, ParseError, Plan, Summary};
+
+// state of the plan screen, kept as the user data of the UI
+struct PlanState {
+  actions: Vec<Action>,
+  filter: Filter,
+  search: Option<Search>,
+  // addresses of collapsed modules
+  collapsed: HashSet<String>,
+  // attributes before and after in columns instead of the diff
+  side_by_side: bool,
+  review: Review,
+  // actions to export, see `Export`
+  selected: HashSet<usize>,
+  export: Option<Export>,
+  colors: bool,
+}
+
+// the selected actions as `-target` or `-replace` arguments, written on exit
+struct Export {
+  flag: &'static str,
+  // stdout if `None`
+  path: Option<String>,
+}
+
+impl Export {
+  fn write(&self, state: &PlanState) -> Result<()> {
+    let mut indices: Vec<&usize> = state.selected.iter().collect();
+    indices.sort();
+
+    let mut text = String::new();
+    for idx in indices {
+      let arg = format!("{}={}", self.flag, state.actions[*idx].reference);
+      text.push_str(&shell_quote(&arg));
+      text.push('\n');
+    }
+
+    match &self.path {
+      Some(path) => fs::write(path, text).with_context(|| format!("writing {}", path)),
+      None => Ok(io::stdout().write_all(text.as_bytes())?),
+    }
+  }
+}
+
+// single quoted for POSIX shells and `xargs`, which removes the quotes too,
+// e.g. `'-target=aws_s3_bucket.this["key"]'`
+fn shell_quote(arg: &str) -> String {
+  format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+impl PlanState {
+  // indices of the actions passing the filter and search
+  fn listed(&self) -> Vec<usize> {
+    self
+      .actions
+      .iter()
+      .enumerate()
+      .filter(|(_, act)| self.filter.shows(&act.typ))
+      .filter(|(_, act)| self.search.as_ref().is_none_or(|search| search.matches(act)))
+      .map(|(idx, _)| idx)
+      .collect()
+  }
+}
+
+/// Which kinds of actions are listed, toggled with `c`, `u`, `d` and `r`.
+#[derive(Debug, Clone, Copy)]
+struct Filter {
+  create: bool,
+  update: bool,
+  destroy: bool,
+  replace: bool,
+}
+
+impl Default for Filter {
+  fn default() -> Self {
+    Filter {
+      create: true,
+      update: true,
+      destroy: true,
+      replace: true,
+    }
+  }
+}
+
+impl Filter {
+  const KEYS: [char; 4] = ['c', 'u', 'd', 'r'];
+
+  fn toggle(&mut self, key: char) {
+    match key {
+      'c' => self.create = !self.create,
+      'u' => self.update = !self.update,
+      'd' => self.destroy = !self.destroy,
+      'r' => self.replace = !self.replace,
+      _ => {}
+    }
+  }
+
+  fn shows(&self, typ: &ActionType) -> bool {
+    match typ {
+      ActionType::Create => self.create,
+      ActionType::Update => self.update,
+      ActionType::Destroy => self.destroy,
+      ActionType::DestroyThenCreate | ActionType::DuplicateThenRemove => self.replace,
+      // reads, moves, imports and forgets are always listed
+      _ => true,
+    }
+  }
+}
+
+impl fmt::Display for Filter {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let toggles = [
+      (self.create, "c", "create"),
+      (self.update, "u", "update"),
+      (self.destroy, "d", "destroy"),
+      (self.replace, "r", "replace"),
+    ];
+
+    for (idx, (shown, key, name)) in toggles.iter().enumerate() {
+      if idx > 0 {
+        write!(f, "  ")?;
+      }
+      write!(f, "[{}] {} {}", if *shown { "x" } else { " " }, key, name)?;
+    }
+
+    Ok(())
+  }
+}
+
+// the `/` search, case insensitive
+struct Search {
+  query: String,
+  regex: Regex,
+  // also match the diff, not only the address
+  content: bool,
+}
+
+impl Search {
+  // `None` clears the search, queries that aren't valid regular expressions
+  // are matched as plain text
+  fn new(query: &str, content: bool) -> Option<Self> {
+    if query.is_empty() {
+      return None;
+    }
+
+    let build = |pattern: &str| RegexBuilder::new(pattern).case_insensitive(true).build();
+    let regex = build(query).or_else(|_| build(&regex::escape(query))).ok()?;
+
+    Some(Search {
+      query: query.to_string(),
+      regex,
+      content,
+    })
+  }
+
+  fn matches(&self, act: &Action) -> bool {
+    [&act.reference, &act.resource, &act.name]
+      .iter()
+      .any(|text| self.regex.is_match(text))
+      || (self.content && self.regex.is_match(&act.content))
+  }
+}
+
+impl fmt::Display for Search {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "/{}/", self.query)?;
+    if self.content {
+      write!(f, " in content")?;
+    }
+    Ok(())
+  }
+}
+
+pub fn run(plan: Plan) -> Result<()> {
+  // always talk to the terminal directly, stdin may be the piped plan
+  let backend = backends::curses::n::Backend::init_with_files("/dev/tty", "/dev/tty")?;
+  // see https://no-color.org
+  let colors = backend.has_colors() && env::var_os("NO_COLOR").is_none();
+
+  let mut ui = Cursive::new();
+  if colors {
+    // the selected row keeps its color, which can't be on the default red
+    ui.update_theme(|theme| theme.palette[PaletteColor::Highlight] = Color::Dark(BaseColor::Black));
+  }
+  ui.add_global_callback('q', |s| s.quit());
+  ui.add_global_callback(Key::Esc, |s| s.quit());
+
+  // each section is its own screen, `Tab` cycles through them
+  let mut screens = 1;
+  if plan.actions.is_empty() {
+    ui.add_layer(no_changes_view(&plan));
+  } else {
+    let title = match plan.warnings.len() {
+      0 => "Plan".to_string(),
+      skipped => format!("Plan ({} skipped, see Warnings)", skipped),
+    };
+    ui.add_layer(
+      LinearLayout::vertical()
+        .child(summary_view(&plan))
+        .child(plan_view(&title))
+        .child(TextView::new("").with_name("status")),
+    );
+
+    ui.set_user_data(PlanState {
+      review: Review::load(&plan.actions),
+      selected: HashSet::new(),
+      export: None,
+      actions: plan.actions,
+      filter: Filter::default(),
+      search: None,
+      collapsed: HashSet::new(),
+      side_by_side: false,
+      colors,
+    });
+    refresh_actions(&mut ui);
+
+    for key in Filter::KEYS {
+      ui.add_global_callback(key, move |s| {
+        if s.active_screen() != 0 {
+          return;
+        }
+        if let Some(state) = s.user_data::<PlanState>() {
+          state.filter.toggle(key);
+        }
+        refresh_actions(s);
+      });
+    }
+
+    ui.add_global_callback('/', |s| {
+      if s.active_screen() == 0 {
+        search_prompt(s);
+      }
+    });
+    ui.add_global_callback('s', |s| {
+      if s.active_screen() != 0 {
+        return;
+      }
+      if let Some(state) = s.user_data::<PlanState>() {
+        state.side_by_side = !state.side_by_side;
+      }
+      refresh_actions(s);
+    });
+    ui.add_global_callback('x', toggle_reviewed);
+    ui.add_global_callback(' ', toggle_selected);
+    ui.add_global_callback('e', export_prompt);
+    ui.add_global_callback('y', |s| yank(s, false));
+    ui.add_global_callback('Y', |s| yank(s, true));
+    ui.add_global_callback('n', |s| step_match(s, true));
+    ui.add_global_callback('N', |s| step_match(s, false));
+  }
+
+  if !plan.drift.is_empty() {
+    screens += 1;
+    ui.add_active_screen();
+    ui.add_layer(actions_view(
+      "Changed outside of Terraform",
+      "drift_content",
+      plan.drift,
+      colors,
+    ));
+  }
+
+  if !plan.outputs.is_empty() {
+    screens += 1;
+    ui.add_active_screen();
+    ui.add_layer(outputs_view(plan.outputs, colors));
+  }
+
+  if !plan.warnings.is_empty() {
+    screens += 1;
+    ui.add_active_screen();
+    ui.add_layer(warnings_view(plan.warnings));
+  }
+
+  ui.set_screen(0);
+
+  ui.set_on_pre_event(Key::Tab, move |s| {
+    // dialogs on top use `Tab` to move between their fields
+    if s.screen().len() > 1 {
+      s.screen_mut().on_event(Event::Key(Key::Tab)).process(s);
+      return;
+    }
+
+    let next = (s.active_screen() + 1) % screens;
+    s.set_screen(next);
+  });
+
+  ui.runner(backend).run();
+
+  match ui.take_user_data::<PlanState>() {
+    Some(state) => match &state.export {
+      Some(export) => export.write(&state),
+      None => Ok(()),
+    },
+    None => Ok(()),
+  }
+}
+
+fn summary_view(plan: &Plan) -> TextView {
+  TextView::new(report::totals(plan))
+}
+
+// also shown when all Actions were skipped or missed, which the totals and
+// warnings point out
+fn no_changes_view(plan: &Plan) -> impl View {
+  let mut text = report::totals(plan);
+  if report::no_changes(plan) {
+    text.insert_str(0, "No changes to resources.\n\n");
+  }
+
+  if !plan.warnings.is_empty() {
+    text.push_str(&format!("\n\n{} action(s) skipped, see Warnings.", plan.warnings.len()));
+  }
+
+  if !plan.drift.is_empty() {
+    text.push_str(&format!(
+      "\n\n{} object(s) changed outside of Terraform.",
+      plan.drift.len()
+    ));
+  }
+
+  if !plan.outputs.is_empty() {
+    text.push_str(&format!("\n\n{} output(s) will change.", plan.outputs.len()));
+  }
+
+  if !plan.drift.is_empty() || !plan.outputs.is_empty() || !plan.warnings.is_empty() {
+    text.push_str("\n\nPress Tab to review them.");
+  }
+
+  Dialog::around(TextView::new(text))
+    .title("Plan")
+    .button("Quit", |s| s.quit())
+}
+
+// a row of the plan list
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+  /// Module address, `Enter` collapses or expands it.
+  Module(String),
+  /// Index into `PlanState::actions`.
+  Action(usize),
+}
+
+// listed actions grouped by module, in the order they appear in the plan
+#[derive(Default)]
+struct Module {
+  address: String,
+  actions: Vec<usize>,
+  modules: Vec<Module>,
+}
+
+impl Module {
+  fn insert(&mut self, path: &[&str], idx: usize) {
+    let (step, rest) = match path.split_first() {
+      Some(split) => split,
+      None => return self.actions.push(idx),
+    };
+
+    let address = match self.address.as_str() {
+      "" => step.to_string(),
+      parent => format!("{}.{}", parent, step),
+    };
+    let pos = match self.modules.iter().position(|module| module.address == address) {
+      Some(pos) => pos,
+      None => {
+        self.modules.push(Module {
+          address,
+          ..Module::default()
+        });
+        self.modules.len() - 1
+      }
+    };
+
+    self.modules[pos].insert(rest, idx);
+  }
+
+  // actions of this and all nested modules
+  fn all_actions(&self) -> Vec<usize> {
+    let mut all = self.actions.clone();
+    for module in &self.modules {
+      all.extend(module.all_actions());
+    }
+    all
+  }
+
+  fn rows(&self, state: &PlanState, depth: usize, rows: &mut Vec<(StyledString, Row)>) {
+    let indent = depth * 2;
+
+    for idx in &self.actions {
+      let act = &state.actions[*idx];
+      let address = act.reference[act.module().len()..].trim_start_matches('.');
+      let check = if state.review.reviewed.contains(&act.reference) {
+        '✓'
+      } else {
+        ' '
+      };
+      let mark = if state.selected.contains(idx) { '*' } else { ' ' };
+      let mut label = StyledString::plain(format!("{}{} {:indent$}", check, mark, "", indent = indent));
+      label.append(action_label(act, address, state.colors));
+      rows.push((label, Row::Action(*idx)));
+    }
+
+    for module in &self.modules {
+      let collapsed = state.collapsed.contains(&module.address);
+      let summary = Summary::count(module.all_actions().into_iter().map(|idx| &state.actions[idx]));
+      let name = module.address[self.address.len()..].trim_start_matches('.');
+      rows.push((
+        StyledString::plain(format!(
+          "   {:indent$}{} {} (+{} ~{} -{})",
+          "",
+          if collapsed { '▸' } else { '▾' },
+          name,
+          summary.add,
+          summary.change,
+          summary.destroy,
+          indent = indent
+        )),
+        Row::Module(module.address.clone()),
+      ));
+      if !collapsed {
+        module.rows(state, depth + 1, rows);
+      }
+    }
+  }
+}
+
+// actions of the plan as a tree of modules
+fn plan_view(title: &str) -> impl View {
+  let select = SelectView::<Row>::new()
+    .on_select(|s, row| show_row(s, Some(row.clone())))
+    .on_submit(|s, row| {
+      if let Row::Module(address) = row {
+        if let Some(state) = s.user_data::<PlanState>() {
+          if !state.collapsed.remove(address) {
+            state.collapsed.insert(address.clone());
+          }
+        }
+        refresh_actions(s);
+      }
+    })
+    .with_name("actions");
+
+  // the full address of the selected row above its content
+  let details = LinearLayout::vertical()
+    .child(TextView::new("").style(Effect::Bold).with_name("address"))
+    .child(TextView::new("").with_name("content").scrollable());
+
+  Panel::new(
+    LinearLayout::horizontal()
+      .child(select)
+      .child(DummyView.fixed_width(2))
+      .child(details.max_width(120)),
+  )
+  .title(title)
+}
+
+// relists the actions after the filter, search or collapsed modules changed,
+// keeping the selection if it's still listed
+fn refresh_actions(s: &mut Cursive) {
+  let (rows, status) = match s.user_data::<PlanState>() {
+    Some(state) => {
+      let listed = state.listed();
+      let mut root = Module::default();
+      for idx in &listed {
+        root.insert(&state.actions[*idx].module_path(), *idx);
+      }
+
+      let mut rows = Vec::new();
+      root.rows(state, 0, &mut rows);
+
+      let reviewed = state
+        .actions
+        .iter()
+        .filter(|act| state.review.reviewed.contains(&act.reference))
+        .count();
+      let mut status = format!(
+        "{}/{} reviewed | {} shown | {}",
+        reviewed,
+        state.actions.len(),
+        listed.len(),
+        state.filter
+      );
+      if let Some(search) = &state.search {
+        status.push_str(&format!(" | {}", search));
+      }
+      if state.side_by_side {
+        status.push_str(" | side by side");
+      }
+      if !state.selected.is_empty() {
+        status.push_str(&format!(" | {} selected", state.selected.len()));
+      }
+      (rows, status)
+    }
+    None => return,
+  };
+
+  let selected = s
+    .call_on_name("actions", |view: &mut SelectView<Row>| {
+      let current = view.selection();
+      view.clear();
+      view.add_all(rows);
+      // otherwise the first action, not the module it's in
+      let pos = current
+        .and_then(|current| view.iter().position(|(_, row)| *row == *current))
+        .or_else(|| view.iter().position(|(_, row)| matches!(row, Row::Action(_))));
+      if let Some(pos) = pos {
+        // the callback only shows the content, done below
+        let _ = view.set_selection(pos);
+      }
+      view.selection().map(|row| (*row).clone())
+    })
+    .flatten();
+
+  show_row(s, selected);
+  s.call_on_name("status", |view: &mut TextView| view.set_content(status));
+}
+
+fn show_row(s: &mut Cursive, row: Option<Row>) {
+  let (address, content) = match (s.user_data::<PlanState>(), row) {
+    (Some(state), Some(Row::Action(idx))) => {
+      let act = &state.actions[idx];
+      let search = state.search.as_ref().map(|search| &search.regex);
+      // e.g. moves have no attributes to compare
+      let content = if state.side_by_side && !act.attributes.is_empty() {
+        render_side_by_side(&act.attributes, search, state.colors)
+      } else {
+        render_content(&action_content(act), search, state.colors)
+      };
+      (act.reference.clone(), content)
+    }
+    (_, Some(Row::Module(address))) => (address, StyledString::new()),
+    _ => (String::new(), StyledString::new()),
+  };
+
+  s.call_on_name("address", |view: &mut TextView| view.set_content(address));
+  s.call_on_name("content", |view: &mut TextView| view.set_content(content));
+}
+
+// Terraform's colors for the change markers, e.g. `+` or `-/+`
+fn marker_style(marker: &str, colors: bool) -> Style {
+  let color = if !colors {
+    return Style::none();
+  } else if marker.starts_with("-/+") || marker.starts_with("+/-") {
+    BaseColor::Magenta
+  } else if marker.starts_with('+') {
+    BaseColor::Green
+  } else if marker.starts_with('-') {
+    BaseColor::Red
+  } else if marker.starts_with('~') {
+    BaseColor::Yellow
+  } else if marker.starts_with("<=") {
+    BaseColor::Cyan
+  } else {
+    return Style::none();
+  };
+
+  Style::from(Color::Dark(color))
+}
+
+// the detail pane, each line colored by its marker, replacement notes stand
+// out even without colors and search hits are in reverse video
+fn render_content(text: &str, search: Option<&Regex>, colors: bool) -> StyledString {
+  let mut styled = StyledString::new();
+  for line in text.split_inclusive('\n') {
+    let note = line
+      .find("# forces replacement")
+      .or_else(|| line.find("# replacement forced by"))
+      .unwrap_or(line.len());
+    let (body, note) = line.split_at(note);
+
+    append_highlighted(&mut styled, body, marker_style(body.trim_start(), colors), search);
+    append_highlighted(&mut styled, note, note_style(colors), search);
+  }
+
+  styled
+}
+
+// replacement notes
+fn note_style(colors: bool) -> Style {
+  if colors {
+    Style::from(Color::Dark(BaseColor::Red)).combine(Effect::Bold)
+  } else {
+    Style::from(Effect::Bold)
+  }
+}
+
+// widest attribute path and value columns of the side by side view, fitting
+// the detail pane
+const PATH_WIDTH: usize = 40;
+const VALUE_WIDTH: usize = 36;
+
+// one row per attribute path with the value before on the left and after on
+// the right, unchanged attributes dimmed (or changed ones bold without colors)
+fn render_side_by_side(attributes: &[Attribute], search: Option<&Regex>, colors: bool) -> StyledString {
+  let mut leaves = Vec::new();
+  collect_leaves(attributes, &mut leaves);
+
+  let paths: Vec<String> = leaves
+    .iter()
+    .map(|attr| match attr.name.as_str() {
+      // list and set elements
+      "" => format!("{}[]", attr.path),
+      _ => attr.path.clone(),
+    })
+    .collect();
+  let path_width = paths
+    .iter()
+    .map(|path| path.chars().count())
+    .max()
+    .unwrap_or_default()
+    .min(PATH_WIDTH);
+
+  let mut styled = StyledString::styled(
+    format!(
+      "    {:path_width$} | {:value_width$} | after\n",
+      "",
+      "before",
+      path_width = path_width,
+      value_width = VALUE_WIDTH
+    ),
+    Effect::Bold,
+  );
+
+  for (attr, path) in leaves.iter().zip(&paths) {
+    let style = if attr.forces_replacement {
+      note_style(colors)
+    } else if attr.change == AttributeChange::Unchanged {
+      match colors {
+        true => Style::from(Color::Light(BaseColor::Black)),
+        false => Style::none(),
+      }
+    } else {
+      match colors {
+        true => marker_style(&attr.change.to_string(), colors),
+        false => Style::from(Effect::Bold),
+      }
+    };
+
+    let cells = [
+      wrap(path, path_width),
+      wrap(attr.old.as_deref().unwrap_or_default(), VALUE_WIDTH),
+      wrap(attr.new.as_deref().unwrap_or_default(), VALUE_WIDTH),
+    ];
+    let height = cells.iter().map(Vec::len).max().unwrap_or_default().max(1);
+    let cell = |column: usize, line: usize| cells[column].get(line).map(String::as_str).unwrap_or_default();
+
+    for line in 0..height {
+      let marker = if line == 0 {
+        attr.change.to_string()
+      } else {
+        String::new()
+      };
+      let text = format!(
+        "{:>3} {:path_width$} | {:value_width$} | {}\n",
+        marker,
+        cell(0, line),
+        cell(1, line),
+        cell(2, line),
+        path_width = path_width,
+        value_width = VALUE_WIDTH
+      );
+      append_highlighted(&mut styled, &text, style, search);
+    }
+  }
+
+  styled
+}
+
+// attributes with values, blocks and collections are flattened into their
+// nested attributes
+fn collect_leaves<'a>(attributes: &'a [Attribute], leaves: &mut Vec<&'a Attribute>) {
+  for attr in attributes {
+    if attr.children.is_empty() {
+      leaves.push(attr);
+    } else {
+      collect_leaves(&attr.children, leaves);
+    }
+  }
+}
+
+// splits each line of `text` into lines of at most `width` characters
+fn wrap(text: &str, width: usize) -> Vec<String> {
+  let mut lines = Vec::new();
+  for line in text.lines() {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+      lines.push(String::new());
+    }
+    lines.extend(chars.chunks(width.max(1)).map(|chunk| chunk.iter().collect()));
+  }
+  lines
+}
+
+fn append_highlighted(styled: &mut StyledString, text: &str, style: Style, search: Option<&Regex>) {
+  let mut end = 0;
+  for hit in search.into_iter().flat_map(|regex| regex.find_iter(text)) {
+    if hit.start() == hit.end() {
+      continue;
+    }
+    styled.append_styled(&text[end..hit.start()], style);
+    styled.append_styled(hit.as_str(), style.combine(Effect::Reverse));
+    end = hit.end();
+  }
+  styled.append_styled(&text[end..], style);
+}
+
+fn search_prompt(s: &mut Cursive) {
+  let (query, content) = match s.user_data::<PlanState>().and_then(|state| state.search.as_ref()) {
+    Some(search) => (search.query.clone(), search.content),
+    None => (String::new(), false),
+  };
+
+  let form = LinearLayout::vertical()
+    .child(
+      EditView::new()
+        .content(query)
+        .on_submit(|s, _| apply_search(s))
+        .with_name("query")
+        .min_width(40),
+    )
+    .child(
+      LinearLayout::horizontal()
+        .child(Checkbox::new().with_checked(content).with_name("search_content"))
+        .child(TextView::new(" also search the diff")),
+    );
+
+  let dialog = Dialog::around(form)
+    .title("Search (empty to clear)")
+    .button("Search", apply_search)
+    .button("Cancel", |s| {
+      s.pop_layer();
+    });
+
+  // `Esc` would quit otherwise
+  s.add_layer(OnEventView::new(dialog).on_event(Key::Esc, |s| {
+    s.pop_layer();
+  }));
+}
+
+fn apply_search(s: &mut Cursive) {
+  let query = s
+    .call_on_name("query", |view: &mut EditView| view.get_content())
+    .unwrap_or_default();
+  let content = s
+    .call_on_name("search_content", |view: &mut Checkbox| view.is_checked())
+    .unwrap_or_default();
+  s.pop_layer();
+
+  if let Some(state) = s.user_data::<PlanState>() {
+    state.search = Search::new(&query, content);
+  }
+  refresh_actions(s);
+}
+
+// marks the selected action as reviewed, or all listed actions of the
+// selected module, or unmarks them if they all were
+fn toggle_reviewed(s: &mut Cursive) {
+  let actions = row_actions(s);
+  let saved = match s.user_data::<PlanState>() {
+    Some(state) if !actions.is_empty() => {
+      let references = actions
+        .iter()
+        .map(|idx| state.actions[*idx].reference.clone())
+        .collect();
+      toggle_all(&mut state.review.reviewed, references);
+      state.review.save()
+    }
+    _ => return,
+  };
+
+  refresh_actions(s);
+  if let Err(err) = saved {
+    s.add_layer(Dialog::info(format!("Could not save the review progress: {}", err)));
+  }
+}
+
+// selects the actions to export, same as `toggle_reviewed`
+fn toggle_selected(s: &mut Cursive) {
+  let actions = row_actions(s);
+  if let Some(state) = s.user_data::<PlanState>() {
+    toggle_all(&mut state.selected, actions);
+  }
+  refresh_actions(s);
+}
+
+// the selected action, or the listed actions of the selected module
+fn row_actions(s: &mut Cursive) -> Vec<usize> {
+  if s.active_screen() != 0 {
+    return Vec::new();
+  }
+
+  let row = s
+    .call_on_name("actions", |view: &mut SelectView<Row>| view.selection())
+    .flatten();
+
+  match (s.user_data::<PlanState>(), row.as_deref()) {
+    (Some(_), Some(Row::Action(idx))) => vec![*idx],
+    (Some(state), Some(Row::Module(address))) => {
+      let prefix = format!("{}.", address);
+      state
+        .listed()
+        .into_iter()
+        .filter(|idx| state.actions[*idx].reference.starts_with(&prefix))
+        .collect()
+    }
+    _ => Vec::new(),
+  }
+}
+
+// adds all of `items`, or removes them if they all were there already
+fn toggle_all<T: Eq + Hash>(set: &mut HashSet<T>, items: Vec<T>) {
+  if items.iter().all(|item| set.contains(item)) {
+    for item in &items {
+      set.remove(item);
+    }
+  } else {
+    set.extend(items);
+  }
+}
+
+fn export_prompt(s: &mut Cursive) {
+  if s.active_screen() != 0 {
+    return;
+  }
+
+  let selected = s.user_data::<PlanState>().map_or(0, |state| state.selected.len());
+  if selected == 0 {
+    s.add_layer(Dialog::info("Select the actions to export with Space first."));
+    return;
+  }
+
+  let form = LinearLayout::vertical()
+    .child(
+      SelectView::new()
+        .item("-target", "-target")
+        .item("-replace", "-replace")
+        .with_name("export_flag"),
+    )
+    .child(DummyView)
+    .child(TextView::new("File, empty for stdout:"))
+    .child(EditView::new().with_name("export_path").min_width(40));
+
+  let dialog = Dialog::around(form)
+    .title(format!("Export {} selected", selected))
+    .button("Export and quit", apply_export)
+    .button("Cancel", |s| {
+      s.pop_layer();
+    });
+
+  // `Esc` would quit otherwise
+  s.add_layer(OnEventView::new(dialog).on_event(Key::Esc, |s| {
+    s.pop_layer();
+  }));
+}
+
+fn apply_export(s: &mut Cursive) {
+  let flag = s
+    .call_on_name("export_flag", |view: &mut SelectView<&'static str>| view.selection())
+    .flatten();
+  let path = s
+    .call_on_name("export_path", |view: &mut EditView| view.get_content())
+    .unwrap_or_default();
+
+  if let (Some(state), Some(flag)) = (s.user_data::<PlanState>(), flag) {
+    state.export = Some(Export {
+      flag: *flag,
+      path: Some(path.to_string()).filter(|path| !path.is_empty()),
+    });
+  }
+  s.quit();
+}
+
+// copies the address of the selected row, or the content of the selected
+// action
+fn yank(s: &mut Cursive, content: bool) {
+  if s.active_screen() != 0 {
+    return;
+  }
+
+  let row = s
+    .call_on_name("actions", |view: &mut SelectView<Row>| view.selection())
+    .flatten();
+  let text = match (s.user_data::<PlanState>(), row.as_deref()) {
+    (Some(state), Some(Row::Action(idx))) if content => action_content(&state.actions[*idx]),
+    (Some(state), Some(Row::Action(idx))) => state.actions[*idx].reference.clone(),
+    (_, Some(Row::Module(address))) if !content => address.clone(),
+    _ => return,
+  };
+
+  let message = match clipboard::copy(&text) {
+    Ok(()) if content => "Copied the content to the clipboard".to_string(),
+    Ok(()) => format!("Copied {} to the clipboard", text),
+    Err(err) => format!("Could not copy to the clipboard: {}", err),
+  };
+  // until the next refresh
+  s.call_on_name("status", |view: &mut TextView| view.set_content(message));
+}
+
+// the list only has matches while searching, so this moves to the next or
+// previous action, skipping modules and wrapping around
+fn step_match(s: &mut Cursive, forward: bool) {
+  if s.active_screen() != 0 || s.user_data::<PlanState>().is_none_or(|state| state.search.is_none()) {
+    return;
+  }
+
+  let callback = s
+    .call_on_name("actions", |view: &mut SelectView<Row>| {
+      let len = view.len();
+      let current = view.selected_id()?;
+      let next = (1..len)
+        .map(|step| {
+          if forward {
+            (current + step) % len
+          } else {
+            (current + len - step) % len
+          }
+        })
+        .find(|&pos| matches!(view.get_item(pos), Some((_, Row::Action(_)))))?;
+      Some(view.set_selection(next))
+    })
+    .flatten();
+
+  if let Some(callback) = callback {
+    callback(s);
+  }
+}
+
+fn action_label(act: &Action, address: &str, colors: bool) -> StyledString {
+  let mut label = format!("{} {}", act.typ, address);
+  if let Some(from) = &act.moved_from {
+    label.push_str(&format!(" (from {})", from));
+  }
+  if !act.forces_replacement.is_empty() {
+    label.push_str(" (forced)");
+  }
+
+  let typ = act.typ.to_string();
+  StyledString::styled(label, marker_style(typ.trim_start(), colors))
+}
+
+fn action_content(act: &Action) -> String {
+  if act.forces_replacement.is_empty() {
+    return act.content.clone();
+  }
+
+  format!(
+    "  # replacement forced by: {}\n\n{}",
+    act.forces_replacement.join(", "),
+    act.content
+  )
+}
+
+fn actions_view(title: &str, content_name: &'static str, actions: Vec<Action>, colors: bool) -> impl View {
+  let items = actions
+    .iter()
+    .map(|act| {
+      (
+        action_label(act, &act.reference, colors),
+        render_content(&action_content(act), None, colors),
+      )
+    })
+    .collect();
+
+  list_view(title, content_name, items)
+}
+
+fn outputs_view(outputs: Vec<Output>, colors: bool) -> impl View {
+  let items = outputs
+    .into_iter()
+    .map(|out| {
+      let mut label = format!("{} {}", out.typ, out.name);
+      if out.sensitive {
+        label.push_str(" (sensitive)");
+      }
+      let typ = out.typ.to_string();
+      (
+        StyledString::styled(label, marker_style(typ.trim_start(), colors)),
+        render_content(&out.content, None, colors),
+      )
+    })
+    .collect();
+
+  list_view("Changes to Outputs", "outputs_content", items)
+}
+
+fn warnings_view(warnings: Vec<ParseError>) -> impl View {
+  let items = warnings
+    .into_iter()
+    .map(|warning| {
+      let label = match warning.line {
+        0 => warning.text.clone(),
+        line => format!("line {}", line),
+      };
+      (StyledString::plain(label), StyledString::plain(warning.to_string()))
+    })
+    .collect();
+
+  list_view("Warnings", "warnings_content", items)
+}
+
+// list of (label, content) with the selected content shown on the right
+fn list_view(title: &str, content_name: &'static str, items: Vec<(StyledString, StyledString)>) -> impl View {
+  let content = TextView::new(items.first().map(|(_, content)| content.clone()).unwrap_or_default())
+    .with_name(content_name)
+    .max_width(120)
+    .scrollable();
+
+  let mut select = SelectView::<StyledString>::new().on_select(move |this_ui, content| {
+    this_ui.call_on_name(content_name, |view: &mut TextView| {
+      view.set_content(content.clone());
+    });
+  });
+
+  for (label, content) in items {
+    select.add_item(label, content);
+  }
+
+  Panel::new(LinearLayout::horizontal().child(select).child(content)).title(title)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shell_quotes() {
+    assert_eq!(
+      shell_quote(r#"-target=aws_s3_bucket.this["my key"]"#),
+      r#"'-target=aws_s3_bucket.this["my key"]'"#
+    );
+    assert_eq!(shell_quote(r#"-target=a.b["it's"]"#), r#"'-target=a.b["it'\''s"]'"#);
+  }
+}